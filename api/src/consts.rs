@@ -1,10 +1,67 @@
+use const_crypto::ed25519;
 use solana_program::{pubkey, pubkey::Pubkey};
 
-/// Miner pubkey
-pub const MINER_PUBKEY: Pubkey = pubkey!("F7coAFJKxeo1btofymv6f6KFmN5LUC9JEGRATRqwQCXL");
+/// The authority allowed to initialize the program.
+pub const INITIALIZER_ADDRESS: Pubkey = pubkey!("HBUh9g46wk2X89CvaNN15UmsznP59rh6od1h8JwYAopk");
+
+/// The seed of the config account PDA.
+pub const CONFIG: &[u8] = b"config";
 
 /// The seed of the escrow account PDA.
 pub const ESCROW: &[u8] = b"escrow";
 
-/// The ore commission the relayer is allowed to collect
-pub const COMMISSION: u64 = 10_000;
+/// The layout version of legacy escrow accounts, which predate versioning.
+pub const ESCROW_LEGACY_VERSION: u64 = 1;
+
+/// The current layout version of escrow accounts.
+pub const ESCROW_VERSION: u64 = 2;
+
+/// The seed of the relayer account PDA.
+pub const RELAYER: &[u8] = b"relayer";
+
+/// The default maximum SOL fee, in lamports, a relayer may be reimbursed per collect.
+pub const DEFAULT_MAX_FEE_PER_COLLECT: u64 = 100_000;
+
+/// The default total SOL fee, in lamports, a relayer may be reimbursed per epoch.
+pub const DEFAULT_EPOCH_FEE_BUDGET: u64 = 50_000_000;
+
+/// The denominator of commission rates, expressed in basis points.
+pub const DENOMINATOR_BPS: u64 = 10_000;
+
+/// The minimum SOL bond, in lamports, a relayer must post to collect.
+pub const MIN_RELAYER_BOND: u64 = 1_000_000_000;
+
+/// The number of slots a relayer bond stays locked after deregistration is requested.
+pub const BOND_LOCKUP_SLOTS: u64 = 216_000;
+
+/// The SOL fee, in lamports, above which a signed collect is evidence of misbehaviour, unless
+/// the escrow's configured cap allows more. No legitimate collect costs this much.
+pub const SLASHABLE_FEE: u64 = 10_000_000;
+
+/// The most lamports which may be slashed from a relayer bond into a single escrow. This bounds
+/// what a relayer can release from its own bond, ahead of the lockup, by slashing itself.
+pub const MAX_ESCROW_SLASH: u64 = 10_000_000;
+
+/// The number of slots an escrow may go without a collect by its relayer before it may revoke it.
+pub const RELAYER_LIVENESS_SLOTS: u64 = 54_000;
+
+/// The number of slots a scheduled miner change waits for the relayer to collect commission
+/// on the current hash.
+pub const MINER_CHANGE_DELAY_SLOTS: u64 = 150;
+
+/// The base fee, in lamports, the runtime charges per transaction signature.
+pub const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+
+/// The address of the compute budget program.
+pub const COMPUTE_BUDGET_PROGRAM_ID: Pubkey =
+    pubkey!("ComputeBudget111111111111111111111111111111");
+
+/// Program id for const pda derivations
+const PROGRAM_ID: [u8; 32] = unsafe { *(&crate::id() as *const Pubkey as *const [u8; 32]) };
+
+/// The address of the config account.
+pub const CONFIG_ADDRESS: Pubkey =
+    Pubkey::new_from_array(ed25519::derive_program_address(&[CONFIG], &PROGRAM_ID).0);
+
+/// The bump of the config account.
+pub const CONFIG_BUMP: u8 = ed25519::derive_program_address(&[CONFIG], &PROGRAM_ID).1;
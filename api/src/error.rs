@@ -1,14 +1,76 @@
-use num_enum::IntoPrimitive;
+use num_enum::{IntoPrimitive, TryFromPrimitive};
 use solana_program::program_error::ProgramError;
 use thiserror::Error;
 
-// TODO Enumerate error types
-
-#[derive(Debug, Error, Clone, Copy, PartialEq, Eq, IntoPrimitive)]
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq, IntoPrimitive, TryFromPrimitive)]
 #[repr(u32)]
 pub enum RelayError {
-    #[error("This is a placeholder error")]
-    Dummy = 0,
+    #[error("The relayer is not authorized to act on this escrow")]
+    UnauthorizedRelayer = 0,
+    #[error("The commission rate must not exceed 10000 basis points")]
+    InvalidCommission = 1,
+    #[error("The requested fee exceeds the maximum fee per collect")]
+    FeeCapExceeded = 2,
+    #[error("The requested fee exceeds the remaining fee budget for this epoch")]
+    EpochBudgetExceeded = 3,
+    #[error("The escrow does not hold enough lamports above rent exemption to pay the fee")]
+    InsufficientEscrowLamports = 4,
+    #[error("Commission has already been collected on the latest hash")]
+    DuplicateCollect = 5,
+    #[error("The escrow balance would underflow")]
+    BalanceUnderflow = 6,
+    #[error("The escrow balance would overflow")]
+    BalanceOverflow = 7,
+    #[error("The miner does not match the relayer miner")]
+    InvalidMiner = 8,
+    #[error("The claim amount exceeds the balance collected by the relayer")]
+    ClaimExceedsCollected = 9,
+    #[error("The escrow account must be migrated to the current layout version")]
+    EscrowOutdated = 10,
+    #[error("The escrow account cannot be migrated from its layout version")]
+    InvalidEscrowVersion = 11,
+    #[error("The relayer has not collected commission on the latest hash")]
+    UncollectedCommission = 12,
+    #[error("The beneficiary does not match the relayer beneficiary")]
+    InvalidBeneficiary = 13,
+    #[error("The escrow has not opted in to compounding")]
+    CompoundDisabled = 14,
+    #[error("The collected balance has not reached the payout threshold")]
+    PayoutThresholdNotMet = 15,
+    #[error("The program is paused")]
+    ProgramPaused = 16,
+    #[error("The relayer is paused")]
+    RelayerPaused = 17,
+    #[error("The signer is not authorized to administer the program")]
+    UnauthorizedAdmin = 18,
+    #[error("The relayer bond is below the minimum")]
+    InsufficientBond = 19,
+    #[error("The relayer bond is still locked")]
+    BondLocked = 20,
+    #[error("The slashing evidence is invalid")]
+    InvalidEvidence = 21,
+    #[error("The signer is not the pending authority of the escrow")]
+    InvalidPendingAuthority = 22,
+    #[error("The relayer has commission which has not been withdrawn")]
+    UnwithdrawnCommission = 23,
+    #[error("The relayer has collected from the escrow within the liveness period")]
+    RelayerLive = 24,
+    #[error("The fees claimed exceed the cost of the transaction")]
+    FeeExceedsTransactionCost = 25,
+    #[error("The rent payer does not match the payer owed the escrow rent debt")]
+    InvalidRentPayer = 26,
+    #[error("Fees may only be claimed by instructions at the top level of a transaction")]
+    FeeClaimedByCpi = 27,
+}
+
+impl RelayError {
+    /// Decodes a relay error from a program error returned by the relay program.
+    pub fn from_program_error(e: &ProgramError) -> Option<Self> {
+        match e {
+            ProgramError::Custom(code) => Self::try_from(*code).ok(),
+            _ => None,
+        }
+    }
 }
 
 impl From<RelayError> for ProgramError {
@@ -0,0 +1,481 @@
+use bytemuck::{Pod, Zeroable};
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use solana_program::{log::sol_log_data, program_error::ProgramError, pubkey::Pubkey};
+
+/// The current encoding version of relay events.
+pub const EVENT_VERSION: u8 = 1;
+
+/// Events are logged as an 8 byte header followed by the event body.
+/// The header holds the event type in its first byte and the encoding version in its second.
+const EVENT_HEADER_LEN: usize = 8;
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, IntoPrimitive, TryFromPrimitive)]
+#[rustfmt::skip]
+pub enum EventType {
+    Claim = 0,
+    CloseEscrow = 1,
+    OpenEscrow = 2,
+    Stake = 3,
+    ConfigureEscrow = 4,
+    MigrateEscrow = 5,
+    SwitchRelayer = 6,
+    ConfigureCompound = 7,
+    ConfigurePayout = 8,
+    SlashRelayer = 9,
+    ProposeAuthority = 10,
+    AcceptAuthority = 11,
+    RevokeRelayer = 12,
+
+    Collect = 101,
+    UpdateMiner = 102,
+    RegisterRelayer = 103,
+    UpdateRelayer = 104,
+    DeregisterRelayer = 105,
+    Compound = 106,
+    Payout = 107,
+    WithdrawCommission = 108,
+
+    Initialize = 200,
+    SetAdmin = 201,
+    SetPaused = 202,
+    SetRelayerPaused = 203,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct AcceptAuthorityEvent {
+    pub escrow: Pubkey,
+    pub previous_authority: Pubkey,
+    pub authority: Pubkey,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct ClaimEvent {
+    pub escrow: Pubkey,
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+    pub last_balance: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct CloseEscrowEvent {
+    pub escrow: Pubkey,
+    pub authority: Pubkey,
+    pub claimed: u64,
+    pub swept: u64,
+    pub commission: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct CollectEvent {
+    pub escrow: Pubkey,
+    pub relayer: Pubkey,
+    pub reward: u64,
+    pub commission: u64,
+    pub fee: u64,
+    pub last_hash: [u8; 32],
+    pub last_balance: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct CompoundEvent {
+    pub escrow: Pubkey,
+    pub relayer: Pubkey,
+    pub amount: u64,
+    pub last_balance: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct ConfigureCompoundEvent {
+    pub escrow: Pubkey,
+    pub compound: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct ConfigureEscrowEvent {
+    pub escrow: Pubkey,
+    pub max_fee_per_collect: u64,
+    pub epoch_fee_budget: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct ConfigurePayoutEvent {
+    pub escrow: Pubkey,
+    pub destination: Pubkey,
+    pub threshold: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct DeregisterRelayerEvent {
+    pub relayer: Pubkey,
+    pub amount: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct InitializeEvent {
+    pub admin: Pubkey,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct MigrateEscrowEvent {
+    pub escrow: Pubkey,
+    pub relayer: Pubkey,
+    pub version: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct OpenEscrowEvent {
+    pub escrow: Pubkey,
+    pub authority: Pubkey,
+    pub relayer: Pubkey,
+    pub commission: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct PayoutEvent {
+    pub escrow: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct ProposeAuthorityEvent {
+    pub escrow: Pubkey,
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct RegisterRelayerEvent {
+    pub relayer: Pubkey,
+    pub authority: Pubkey,
+    pub miner: Pubkey,
+    pub beneficiary: Pubkey,
+    pub commission: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct RevokeRelayerEvent {
+    pub escrow: Pubkey,
+    pub relayer: Pubkey,
+    pub miner: Pubkey,
+    pub last_active_slot: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct SetAdminEvent {
+    pub admin: Pubkey,
+    pub new_admin: Pubkey,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct SetPausedEvent {
+    pub paused: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct SetRelayerPausedEvent {
+    pub relayer: Pubkey,
+    pub admin_paused: u64,
+    pub self_paused: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct SlashRelayerEvent {
+    pub escrow: Pubkey,
+    pub relayer: Pubkey,
+    pub fee: u64,
+    pub amount: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct StakeEvent {
+    pub escrow: Pubkey,
+    pub amount: u64,
+    pub last_balance: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct SwitchRelayerEvent {
+    pub escrow: Pubkey,
+    pub relayer: Pubkey,
+    pub new_relayer: Pubkey,
+    pub commission: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct UpdateMinerEvent {
+    pub escrow: Pubkey,
+    pub miner: Pubkey,
+    pub effective_slot: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct UpdateRelayerEvent {
+    pub relayer: Pubkey,
+    pub beneficiary: Pubkey,
+    pub commission: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct WithdrawCommissionEvent {
+    pub relayer: Pubkey,
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+}
+
+/// A decoded relay event.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RelayEvent {
+    AcceptAuthority(AcceptAuthorityEvent),
+    Claim(ClaimEvent),
+    CloseEscrow(CloseEscrowEvent),
+    Collect(CollectEvent),
+    Compound(CompoundEvent),
+    ConfigureCompound(ConfigureCompoundEvent),
+    ConfigureEscrow(ConfigureEscrowEvent),
+    ConfigurePayout(ConfigurePayoutEvent),
+    DeregisterRelayer(DeregisterRelayerEvent),
+    Initialize(InitializeEvent),
+    MigrateEscrow(MigrateEscrowEvent),
+    OpenEscrow(OpenEscrowEvent),
+    Payout(PayoutEvent),
+    ProposeAuthority(ProposeAuthorityEvent),
+    RegisterRelayer(RegisterRelayerEvent),
+    RevokeRelayer(RevokeRelayerEvent),
+    SetAdmin(SetAdminEvent),
+    SetPaused(SetPausedEvent),
+    SetRelayerPaused(SetRelayerPausedEvent),
+    SlashRelayer(SlashRelayerEvent),
+    Stake(StakeEvent),
+    SwitchRelayer(SwitchRelayerEvent),
+    UpdateMiner(UpdateMinerEvent),
+    UpdateRelayer(UpdateRelayerEvent),
+    WithdrawCommission(WithdrawCommissionEvent),
+}
+
+impl RelayEvent {
+    /// Decodes an event from the data of a `Program data:` log, after base64 decoding.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len().lt(&EVENT_HEADER_LEN) || data[1].ne(&EVENT_VERSION) {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let body = &data[EVENT_HEADER_LEN..];
+        let event = match EventType::try_from(data[0]).or(Err(ProgramError::InvalidArgument))? {
+            EventType::AcceptAuthority => {
+                Self::AcceptAuthority(AcceptAuthorityEvent::try_from_bytes(body)?)
+            }
+            EventType::Claim => Self::Claim(ClaimEvent::try_from_bytes(body)?),
+            EventType::CloseEscrow => Self::CloseEscrow(CloseEscrowEvent::try_from_bytes(body)?),
+            EventType::Collect => Self::Collect(CollectEvent::try_from_bytes(body)?),
+            EventType::Compound => Self::Compound(CompoundEvent::try_from_bytes(body)?),
+            EventType::ConfigureCompound => {
+                Self::ConfigureCompound(ConfigureCompoundEvent::try_from_bytes(body)?)
+            }
+            EventType::ConfigureEscrow => {
+                Self::ConfigureEscrow(ConfigureEscrowEvent::try_from_bytes(body)?)
+            }
+            EventType::ConfigurePayout => {
+                Self::ConfigurePayout(ConfigurePayoutEvent::try_from_bytes(body)?)
+            }
+            EventType::DeregisterRelayer => {
+                Self::DeregisterRelayer(DeregisterRelayerEvent::try_from_bytes(body)?)
+            }
+            EventType::Initialize => Self::Initialize(InitializeEvent::try_from_bytes(body)?),
+            EventType::MigrateEscrow => {
+                Self::MigrateEscrow(MigrateEscrowEvent::try_from_bytes(body)?)
+            }
+            EventType::OpenEscrow => Self::OpenEscrow(OpenEscrowEvent::try_from_bytes(body)?),
+            EventType::Payout => Self::Payout(PayoutEvent::try_from_bytes(body)?),
+            EventType::ProposeAuthority => {
+                Self::ProposeAuthority(ProposeAuthorityEvent::try_from_bytes(body)?)
+            }
+            EventType::RegisterRelayer => {
+                Self::RegisterRelayer(RegisterRelayerEvent::try_from_bytes(body)?)
+            }
+            EventType::RevokeRelayer => {
+                Self::RevokeRelayer(RevokeRelayerEvent::try_from_bytes(body)?)
+            }
+            EventType::SetAdmin => Self::SetAdmin(SetAdminEvent::try_from_bytes(body)?),
+            EventType::SetPaused => Self::SetPaused(SetPausedEvent::try_from_bytes(body)?),
+            EventType::SetRelayerPaused => {
+                Self::SetRelayerPaused(SetRelayerPausedEvent::try_from_bytes(body)?)
+            }
+            EventType::SlashRelayer => Self::SlashRelayer(SlashRelayerEvent::try_from_bytes(body)?),
+            EventType::Stake => Self::Stake(StakeEvent::try_from_bytes(body)?),
+            EventType::SwitchRelayer => {
+                Self::SwitchRelayer(SwitchRelayerEvent::try_from_bytes(body)?)
+            }
+            EventType::UpdateMiner => Self::UpdateMiner(UpdateMinerEvent::try_from_bytes(body)?),
+            EventType::UpdateRelayer => {
+                Self::UpdateRelayer(UpdateRelayerEvent::try_from_bytes(body)?)
+            }
+            EventType::WithdrawCommission => {
+                Self::WithdrawCommission(WithdrawCommissionEvent::try_from_bytes(body)?)
+            }
+        };
+        Ok(event)
+    }
+}
+
+macro_rules! impl_event {
+    ($struct_name:ident, $event_type:expr) => {
+        impl $struct_name {
+            /// Logs the event with its header.
+            pub fn log(&self) {
+                let mut header = [0u8; EVENT_HEADER_LEN];
+                header[0] = $event_type.into();
+                header[1] = EVENT_VERSION;
+                sol_log_data(&[&[&header[..], bytemuck::bytes_of(self)].concat()]);
+            }
+
+            fn try_from_bytes(data: &[u8]) -> Result<Self, ProgramError> {
+                bytemuck::try_pod_read_unaligned::<Self>(data)
+                    .or(Err(ProgramError::InvalidArgument))
+            }
+        }
+    };
+}
+
+impl_event!(AcceptAuthorityEvent, EventType::AcceptAuthority);
+impl_event!(ClaimEvent, EventType::Claim);
+impl_event!(CloseEscrowEvent, EventType::CloseEscrow);
+impl_event!(CollectEvent, EventType::Collect);
+impl_event!(CompoundEvent, EventType::Compound);
+impl_event!(ConfigureCompoundEvent, EventType::ConfigureCompound);
+impl_event!(ConfigureEscrowEvent, EventType::ConfigureEscrow);
+impl_event!(ConfigurePayoutEvent, EventType::ConfigurePayout);
+impl_event!(DeregisterRelayerEvent, EventType::DeregisterRelayer);
+impl_event!(InitializeEvent, EventType::Initialize);
+impl_event!(MigrateEscrowEvent, EventType::MigrateEscrow);
+impl_event!(OpenEscrowEvent, EventType::OpenEscrow);
+impl_event!(PayoutEvent, EventType::Payout);
+impl_event!(ProposeAuthorityEvent, EventType::ProposeAuthority);
+impl_event!(RegisterRelayerEvent, EventType::RegisterRelayer);
+impl_event!(RevokeRelayerEvent, EventType::RevokeRelayer);
+impl_event!(SetAdminEvent, EventType::SetAdmin);
+impl_event!(SetPausedEvent, EventType::SetPaused);
+impl_event!(SetRelayerPausedEvent, EventType::SetRelayerPaused);
+impl_event!(SlashRelayerEvent, EventType::SlashRelayer);
+impl_event!(StakeEvent, EventType::Stake);
+impl_event!(SwitchRelayerEvent, EventType::SwitchRelayer);
+impl_event!(UpdateMinerEvent, EventType::UpdateMiner);
+impl_event!(UpdateRelayerEvent, EventType::UpdateRelayer);
+impl_event!(WithdrawCommissionEvent, EventType::WithdrawCommission);
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use solana_program::program_stubs::{set_syscall_stubs, SyscallStubs};
+
+    use super::*;
+
+    thread_local! {
+        static LOGGED: RefCell<Vec<Vec<u8>>> = const { RefCell::new(vec![]) };
+    }
+
+    /// Captures data logs instead of printing them.
+    struct LogStubs;
+
+    impl SyscallStubs for LogStubs {
+        fn sol_log_data(&self, data: &[&[u8]]) {
+            LOGGED.with(|logged| logged.borrow_mut().push(data.concat()));
+        }
+    }
+
+    /// Logs an event with distinct bytes in every field, and returns the logged data.
+    fn logged<T: Pod>(log: impl Fn(&T)) -> (T, Vec<u8>) {
+        set_syscall_stubs(Box::new(LogStubs));
+        let mut event = T::zeroed();
+        for (i, byte) in bytemuck::bytes_of_mut(&mut event).iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        log(&event);
+        let data = LOGGED.with(|logged| logged.borrow_mut().pop()).unwrap();
+        (event, data)
+    }
+
+    macro_rules! assert_round_trip {
+        ($struct_name:ident, $variant:ident) => {
+            let (event, data) = logged(|e: &$struct_name| e.log());
+            assert_eq!(
+                RelayEvent::from_bytes(&data),
+                Ok(RelayEvent::$variant(event))
+            );
+        };
+    }
+
+    #[test]
+    fn test_from_bytes() {
+        assert_round_trip!(AcceptAuthorityEvent, AcceptAuthority);
+        assert_round_trip!(ClaimEvent, Claim);
+        assert_round_trip!(CloseEscrowEvent, CloseEscrow);
+        assert_round_trip!(CollectEvent, Collect);
+        assert_round_trip!(CompoundEvent, Compound);
+        assert_round_trip!(ConfigureCompoundEvent, ConfigureCompound);
+        assert_round_trip!(ConfigureEscrowEvent, ConfigureEscrow);
+        assert_round_trip!(ConfigurePayoutEvent, ConfigurePayout);
+        assert_round_trip!(DeregisterRelayerEvent, DeregisterRelayer);
+        assert_round_trip!(InitializeEvent, Initialize);
+        assert_round_trip!(MigrateEscrowEvent, MigrateEscrow);
+        assert_round_trip!(OpenEscrowEvent, OpenEscrow);
+        assert_round_trip!(PayoutEvent, Payout);
+        assert_round_trip!(ProposeAuthorityEvent, ProposeAuthority);
+        assert_round_trip!(RegisterRelayerEvent, RegisterRelayer);
+        assert_round_trip!(RevokeRelayerEvent, RevokeRelayer);
+        assert_round_trip!(SetAdminEvent, SetAdmin);
+        assert_round_trip!(SetPausedEvent, SetPaused);
+        assert_round_trip!(SetRelayerPausedEvent, SetRelayerPaused);
+        assert_round_trip!(SlashRelayerEvent, SlashRelayer);
+        assert_round_trip!(StakeEvent, Stake);
+        assert_round_trip!(SwitchRelayerEvent, SwitchRelayer);
+        assert_round_trip!(UpdateMinerEvent, UpdateMiner);
+        assert_round_trip!(UpdateRelayerEvent, UpdateRelayer);
+        assert_round_trip!(WithdrawCommissionEvent, WithdrawCommission);
+    }
+
+    #[test]
+    fn test_from_bytes_rejects_malformed_data() {
+        let (_, data) = logged(|e: &ClaimEvent| e.log());
+        assert_eq!(&data[..2], &[EventType::Claim.into(), EVENT_VERSION]);
+
+        // Unknown versions and event types
+        let mut version = data.clone();
+        version[1] = EVENT_VERSION + 1;
+        assert!(RelayEvent::from_bytes(&version).is_err());
+        let mut event_type = data.clone();
+        event_type[0] = 255;
+        assert!(RelayEvent::from_bytes(&event_type).is_err());
+
+        // Truncated data
+        assert!(RelayEvent::from_bytes(&data[..data.len() - 1]).is_err());
+        assert!(RelayEvent::from_bytes(&data[..EVENT_HEADER_LEN - 1]).is_err());
+    }
+}
@@ -1,6 +1,7 @@
 use bytemuck::{Pod, Zeroable};
+use drillx::Solution;
 use num_enum::TryFromPrimitive;
-use ore_api::consts::*;
+use ore_api::consts::{CONFIG_ADDRESS as ORE_CONFIG_ADDRESS, MINT_ADDRESS, PROOF};
 use ore_utils::{impl_instruction_from_bytes, impl_to_bytes};
 use solana_program::{
     instruction::{AccountMeta, Instruction},
@@ -18,9 +19,31 @@ pub enum RelayInstruction {
     CloseEscrow = 1,
     OpenEscrow = 2,
     Stake = 3,
+    ConfigureEscrow = 4,
+    MigrateEscrow = 5,
+    SwitchRelayer = 6,
+    ConfigureCompound = 7,
+    ConfigurePayout = 8,
+    SlashRelayer = 9,
+    ProposeAuthority = 10,
+    AcceptAuthority = 11,
+    RevokeRelayer = 12,
 
     Collect = 101, 
     UpdateMiner = 102, 
+    RegisterRelayer = 103,
+    UpdateRelayer = 104,
+    DeregisterRelayer = 105,
+    Compound = 106,
+    Payout = 107,
+    WithdrawCommission = 108,
+    Mine = 109,
+    CollectMany = 110,
+
+    Initialize = 200,
+    SetAdmin = 201,
+    SetPaused = 202,
+    SetRelayerPaused = 203,
 }
 
 #[repr(C)]
@@ -35,11 +58,79 @@ pub struct CollectArgs {
     pub fee: [u8; 8],
 }
 
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct CollectManyArgs {
+    pub fee: [u8; 8],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct CompoundArgs {
+    pub fee: [u8; 8],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct ConfigureCompoundArgs {
+    pub compound: u8,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct ConfigureEscrowArgs {
+    pub max_fee_per_collect: [u8; 8],
+    pub epoch_fee_budget: [u8; 8],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct ConfigurePayoutArgs {
+    pub threshold: [u8; 8],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct MigrateEscrowArgs {
+    pub expected_commission: [u8; 8],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct MineArgs {
+    pub digest: [u8; 16],
+    pub nonce: [u8; 8],
+    pub fee: [u8; 8],
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Pod, Zeroable)]
 pub struct OpenEscrowArgs {
     pub escrow_bump: u8,
     pub proof_bump: u8,
+    pub relayer: Pubkey,
+    pub commission: [u8; 8],
+    pub rent_debt: [u8; 8],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct PayoutArgs {
+    pub fee: [u8; 8],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct RegisterRelayerArgs {
+    pub bump: u8,
+    pub commission: [u8; 8],
+    pub bond: [u8; 8],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct SetPausedArgs {
+    pub paused: u8,
 }
 
 #[repr(C)]
@@ -48,6 +139,18 @@ pub struct StakeArgs {
     pub amount: [u8; 8],
 }
 
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct UpdateRelayerArgs {
+    pub commission: [u8; 8],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct SwitchRelayerArgs {
+    pub commission: [u8; 8],
+}
+
 impl RelayInstruction {
     pub fn to_vec(&self) -> Vec<u8> {
         vec![*self as u8]
@@ -56,38 +159,108 @@ impl RelayInstruction {
 
 impl_to_bytes!(ClaimArgs);
 impl_to_bytes!(CollectArgs);
+impl_to_bytes!(CollectManyArgs);
+impl_to_bytes!(CompoundArgs);
+impl_to_bytes!(ConfigureCompoundArgs);
+impl_to_bytes!(ConfigureEscrowArgs);
+impl_to_bytes!(ConfigurePayoutArgs);
+impl_to_bytes!(MigrateEscrowArgs);
+impl_to_bytes!(MineArgs);
 impl_to_bytes!(OpenEscrowArgs);
+impl_to_bytes!(PayoutArgs);
+impl_to_bytes!(RegisterRelayerArgs);
+impl_to_bytes!(SetPausedArgs);
 impl_to_bytes!(StakeArgs);
+impl_to_bytes!(SwitchRelayerArgs);
+impl_to_bytes!(UpdateRelayerArgs);
 
 impl_instruction_from_bytes!(ClaimArgs);
 impl_instruction_from_bytes!(CollectArgs);
+impl_instruction_from_bytes!(CollectManyArgs);
+impl_instruction_from_bytes!(CompoundArgs);
+impl_instruction_from_bytes!(ConfigureCompoundArgs);
+impl_instruction_from_bytes!(ConfigureEscrowArgs);
+impl_instruction_from_bytes!(ConfigurePayoutArgs);
+impl_instruction_from_bytes!(MigrateEscrowArgs);
+impl_instruction_from_bytes!(MineArgs);
 impl_instruction_from_bytes!(OpenEscrowArgs);
+impl_instruction_from_bytes!(PayoutArgs);
+impl_instruction_from_bytes!(RegisterRelayerArgs);
+impl_instruction_from_bytes!(SetPausedArgs);
 impl_instruction_from_bytes!(StakeArgs);
+impl_instruction_from_bytes!(SwitchRelayerArgs);
+impl_instruction_from_bytes!(UpdateRelayerArgs);
+
+// Builds a close_escrow instruction.
+// The relayer vault receives commission on rewards which have not been collected. The rent
+// payer is repaid the rent debt, and may be the signer if none is owed.
+pub fn close_escrow(
+    signer: Pubkey,
+    seed_authority: Pubkey,
+    beneficiary: Pubkey,
+    relayer: Pubkey,
+    rent_payer: Pubkey,
+) -> Instruction {
+    let (escrow_pda, _) =
+        Pubkey::find_program_address(&[ESCROW, seed_authority.as_ref()], &crate::id());
+    let relayer_vault =
+        spl_associated_token_account::get_associated_token_address(&relayer, &MINT_ADDRESS);
+    let escrow_tokens =
+        spl_associated_token_account::get_associated_token_address(&escrow_pda, &MINT_ADDRESS);
+    let (proof_pda, _) =
+        Pubkey::find_program_address(&[PROOF, escrow_pda.as_ref()], &ore_api::id());
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(signer, true),
+            AccountMeta::new(beneficiary, false),
+            AccountMeta::new(relayer, false),
+            AccountMeta::new(relayer_vault, false),
+            AccountMeta::new(rent_payer, false),
+            AccountMeta::new(escrow_pda, false),
+            AccountMeta::new(escrow_tokens, false),
+            AccountMeta::new(proof_pda, false),
+            AccountMeta::new_readonly(ore_api::consts::TREASURY_ADDRESS, false),
+            AccountMeta::new(ore_api::consts::TREASURY_TOKENS_ADDRESS, false),
+            AccountMeta::new_readonly(ore_api::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: RelayInstruction::CloseEscrow.to_vec(),
+    }
+}
 
 // Builds a collect instruction.
+// Commission is claimed into the relayer vault until it is withdrawn with withdraw_commission.
+// The fees claimed by the transaction may not exceed its signature and priority fees.
 pub fn collect(
     signer: Pubkey,
-    escrow_authority: Pubkey,
-    beneficiary: Pubkey,
+    relayer: Pubkey,
+    seed_authority: Pubkey,
     sol_fee: u64,
 ) -> Instruction {
     let (escrow_pda, _) =
-        Pubkey::find_program_address(&[ESCROW, escrow_authority.as_ref()], &crate::id());
+        Pubkey::find_program_address(&[ESCROW, seed_authority.as_ref()], &crate::id());
     let (proof_pda, _) =
         Pubkey::find_program_address(&[PROOF, escrow_pda.as_ref()], &ore_api::id());
+    let relayer_vault =
+        spl_associated_token_account::get_associated_token_address(&relayer, &MINT_ADDRESS);
     Instruction {
         program_id: crate::id(),
         accounts: vec![
             AccountMeta::new(signer, true),
-            AccountMeta::new(beneficiary, false),
+            AccountMeta::new_readonly(CONFIG_ADDRESS, false),
+            AccountMeta::new(relayer, false),
             AccountMeta::new(escrow_pda, false),
             AccountMeta::new(proof_pda, false),
+            AccountMeta::new(relayer_vault, false),
             AccountMeta::new_readonly(ore_api::consts::TREASURY_ADDRESS, false),
             AccountMeta::new(ore_api::consts::TREASURY_TOKENS_ADDRESS, false),
             AccountMeta::new_readonly(ore_api::id(), false),
             AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(sysvar::instructions::id(), false),
         ],
-        data: vec![
+        data: [
             RelayInstruction::Collect.to_vec(),
             CollectArgs {
                 fee: sol_fee.to_le_bytes(),
@@ -99,9 +272,59 @@ pub fn collect(
     }
 }
 
+// Builds a compound instruction.
+// Collects commission like collect, and restakes the net reward into the proof.
+pub fn compound(
+    signer: Pubkey,
+    relayer: Pubkey,
+    seed_authority: Pubkey,
+    sol_fee: u64,
+) -> Instruction {
+    let (escrow_pda, _) =
+        Pubkey::find_program_address(&[ESCROW, seed_authority.as_ref()], &crate::id());
+    let escrow_tokens =
+        spl_associated_token_account::get_associated_token_address(&escrow_pda, &MINT_ADDRESS);
+    let (proof_pda, _) =
+        Pubkey::find_program_address(&[PROOF, escrow_pda.as_ref()], &ore_api::id());
+    let relayer_vault =
+        spl_associated_token_account::get_associated_token_address(&relayer, &MINT_ADDRESS);
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(signer, true),
+            AccountMeta::new_readonly(CONFIG_ADDRESS, false),
+            AccountMeta::new(relayer, false),
+            AccountMeta::new(escrow_pda, false),
+            AccountMeta::new(proof_pda, false),
+            AccountMeta::new(relayer_vault, false),
+            AccountMeta::new(escrow_tokens, false),
+            AccountMeta::new_readonly(ore_api::consts::TREASURY_ADDRESS, false),
+            AccountMeta::new(ore_api::consts::TREASURY_TOKENS_ADDRESS, false),
+            AccountMeta::new_readonly(ore_api::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(sysvar::instructions::id(), false),
+        ],
+        data: [
+            RelayInstruction::Compound.to_vec(),
+            CompoundArgs {
+                fee: sol_fee.to_le_bytes(),
+            }
+            .to_bytes()
+            .to_vec(),
+        ]
+        .concat(),
+    }
+}
+
 // Builds a claim instruction.
-pub fn claim(signer: Pubkey, beneficiary: Pubkey, amount: u64) -> Instruction {
-    let (escrow_pda, _) = Pubkey::find_program_address(&[ESCROW, signer.as_ref()], &crate::id());
+pub fn claim(
+    signer: Pubkey,
+    seed_authority: Pubkey,
+    beneficiary: Pubkey,
+    amount: u64,
+) -> Instruction {
+    let (escrow_pda, _) =
+        Pubkey::find_program_address(&[ESCROW, seed_authority.as_ref()], &crate::id());
     let (proof_pda, _) =
         Pubkey::find_program_address(&[PROOF, escrow_pda.as_ref()], &ore_api::id());
     Instruction {
@@ -129,8 +352,9 @@ pub fn claim(signer: Pubkey, beneficiary: Pubkey, amount: u64) -> Instruction {
 }
 
 // Builds a stake instruction.
-pub fn stake(signer: Pubkey, sender: Pubkey, amount: u64) -> Instruction {
-    let (escrow_pda, _) = Pubkey::find_program_address(&[ESCROW, signer.as_ref()], &crate::id());
+pub fn stake(signer: Pubkey, seed_authority: Pubkey, sender: Pubkey, amount: u64) -> Instruction {
+    let (escrow_pda, _) =
+        Pubkey::find_program_address(&[ESCROW, seed_authority.as_ref()], &crate::id());
     let escrow_tokens =
         spl_associated_token_account::get_associated_token_address(&escrow_pda, &MINT_ADDRESS);
     let (proof_pda, _) =
@@ -139,6 +363,7 @@ pub fn stake(signer: Pubkey, sender: Pubkey, amount: u64) -> Instruction {
         program_id: crate::id(),
         accounts: vec![
             AccountMeta::new(signer, true),
+            AccountMeta::new_readonly(CONFIG_ADDRESS, false),
             AccountMeta::new(escrow_pda, false),
             AccountMeta::new(escrow_tokens, false),
             AccountMeta::new(proof_pda, false),
@@ -160,7 +385,16 @@ pub fn stake(signer: Pubkey, sender: Pubkey, amount: u64) -> Instruction {
 }
 
 // Builds an open_escrow instruction.
-pub fn open_escrow(signer: Pubkey, payer: Pubkey) -> Instruction {
+// The payer funds every account opened. A payer other than the signer may record up to the given
+// rent debt, in lamports, to be repaid from the escrow rent when it closes.
+pub fn open_escrow(
+    signer: Pubkey,
+    payer: Pubkey,
+    relayer: Pubkey,
+    miner: Pubkey,
+    commission: u64,
+    rent_debt: u64,
+) -> Instruction {
     let escrow_pda = Pubkey::find_program_address(&[ESCROW, signer.as_ref()], &crate::id());
     let proof_pda = Pubkey::find_program_address(&[PROOF, escrow_pda.0.as_ref()], &ore_api::id());
     let escrow_tokens_address =
@@ -169,7 +403,9 @@ pub fn open_escrow(signer: Pubkey, payer: Pubkey) -> Instruction {
         program_id: crate::id(),
         accounts: vec![
             AccountMeta::new(signer, true),
-            AccountMeta::new_readonly(signer, false),
+            AccountMeta::new_readonly(CONFIG_ADDRESS, false),
+            AccountMeta::new_readonly(miner, false),
+            AccountMeta::new_readonly(relayer, false),
             AccountMeta::new(payer, true),
             AccountMeta::new(escrow_pda.0, false),
             AccountMeta::new(escrow_tokens_address, false),
@@ -186,6 +422,9 @@ pub fn open_escrow(signer: Pubkey, payer: Pubkey) -> Instruction {
             OpenEscrowArgs {
                 escrow_bump: escrow_pda.1,
                 proof_bump: proof_pda.1,
+                relayer,
+                commission: commission.to_le_bytes(),
+                rent_debt: rent_debt.to_le_bytes(),
             }
             .to_bytes()
             .to_vec(),
@@ -194,14 +433,242 @@ pub fn open_escrow(signer: Pubkey, payer: Pubkey) -> Instruction {
     }
 }
 
-// Builds an update_miner instruction.
-pub fn update_miner(signer: Pubkey, miner: Pubkey) -> Instruction {
+// Builds a collect_many instruction.
+// Escrows are addressed by their PDA, and each is paired with its proof. The fee is reimbursed
+// by each escrow collected.
+pub fn collect_many(
+    signer: Pubkey,
+    relayer: Pubkey,
+    escrows: &[Pubkey],
+    sol_fee: u64,
+) -> Instruction {
+    let relayer_vault =
+        spl_associated_token_account::get_associated_token_address(&relayer, &MINT_ADDRESS);
+    let mut accounts = vec![
+        AccountMeta::new(signer, true),
+        AccountMeta::new_readonly(CONFIG_ADDRESS, false),
+        AccountMeta::new(relayer, false),
+        AccountMeta::new(relayer_vault, false),
+        AccountMeta::new_readonly(ore_api::consts::TREASURY_ADDRESS, false),
+        AccountMeta::new(ore_api::consts::TREASURY_TOKENS_ADDRESS, false),
+        AccountMeta::new_readonly(ore_api::id(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(sysvar::instructions::id(), false),
+    ];
+    for escrow in escrows {
+        let proof_pda = Pubkey::find_program_address(&[PROOF, escrow.as_ref()], &ore_api::id());
+        accounts.push(AccountMeta::new(*escrow, false));
+        accounts.push(AccountMeta::new(proof_pda.0, false));
+    }
+    Instruction {
+        program_id: crate::id(),
+        accounts,
+        data: [
+            RelayInstruction::CollectMany.to_vec(),
+            CollectManyArgs {
+                fee: sol_fee.to_le_bytes(),
+            }
+            .to_bytes()
+            .to_vec(),
+        ]
+        .concat(),
+    }
+}
+
+// Builds a configure_escrow instruction.
+pub fn configure_escrow(
+    signer: Pubkey,
+    seed_authority: Pubkey,
+    max_fee_per_collect: u64,
+    epoch_fee_budget: u64,
+) -> Instruction {
+    let escrow_pda = Pubkey::find_program_address(&[ESCROW, seed_authority.as_ref()], &crate::id());
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(signer, true),
+            AccountMeta::new(escrow_pda.0, false),
+        ],
+        data: [
+            RelayInstruction::ConfigureEscrow.to_vec(),
+            ConfigureEscrowArgs {
+                max_fee_per_collect: max_fee_per_collect.to_le_bytes(),
+                epoch_fee_budget: epoch_fee_budget.to_le_bytes(),
+            }
+            .to_bytes()
+            .to_vec(),
+        ]
+        .concat(),
+    }
+}
+
+// Builds a configure_compound instruction.
+pub fn configure_compound(signer: Pubkey, seed_authority: Pubkey, compound: bool) -> Instruction {
+    let escrow_pda = Pubkey::find_program_address(&[ESCROW, seed_authority.as_ref()], &crate::id());
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(signer, true),
+            AccountMeta::new(escrow_pda.0, false),
+        ],
+        data: [
+            RelayInstruction::ConfigureCompound.to_vec(),
+            ConfigureCompoundArgs {
+                compound: compound as u8,
+            }
+            .to_bytes()
+            .to_vec(),
+        ]
+        .concat(),
+    }
+}
+
+// Builds a migrate_escrow instruction.
+// Legacy escrows adopt the relayer commission, which must equal the expected commission.
+pub fn migrate_escrow(signer: Pubkey, relayer: Pubkey, expected_commission: u64) -> Instruction {
     let escrow_pda = Pubkey::find_program_address(&[ESCROW, signer.as_ref()], &crate::id());
     let proof_pda = Pubkey::find_program_address(&[PROOF, escrow_pda.0.as_ref()], &ore_api::id());
     Instruction {
         program_id: crate::id(),
         accounts: vec![
             AccountMeta::new(signer, true),
+            AccountMeta::new_readonly(CONFIG_ADDRESS, false),
+            AccountMeta::new(escrow_pda.0, false),
+            AccountMeta::new_readonly(proof_pda.0, false),
+            AccountMeta::new_readonly(relayer, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: [
+            RelayInstruction::MigrateEscrow.to_vec(),
+            MigrateEscrowArgs {
+                expected_commission: expected_commission.to_le_bytes(),
+            }
+            .to_bytes()
+            .to_vec(),
+        ]
+        .concat(),
+    }
+}
+
+// Builds a switch_relayer instruction.
+// The vault of the current relayer receives commission on rewards which have not been collected.
+pub fn switch_relayer(
+    signer: Pubkey,
+    seed_authority: Pubkey,
+    relayer: Pubkey,
+    new_relayer: Pubkey,
+    miner: Pubkey,
+    commission: u64,
+) -> Instruction {
+    let escrow_pda = Pubkey::find_program_address(&[ESCROW, seed_authority.as_ref()], &crate::id());
+    let proof_pda = Pubkey::find_program_address(&[PROOF, escrow_pda.0.as_ref()], &ore_api::id());
+    let relayer_vault =
+        spl_associated_token_account::get_associated_token_address(&relayer, &MINT_ADDRESS);
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(signer, true),
+            AccountMeta::new_readonly(CONFIG_ADDRESS, false),
+            AccountMeta::new(escrow_pda.0, false),
+            AccountMeta::new(proof_pda.0, false),
+            AccountMeta::new(relayer, false),
+            AccountMeta::new(relayer_vault, false),
+            AccountMeta::new_readonly(new_relayer, false),
+            AccountMeta::new_readonly(miner, false),
+            AccountMeta::new_readonly(ore_api::consts::TREASURY_ADDRESS, false),
+            AccountMeta::new(ore_api::consts::TREASURY_TOKENS_ADDRESS, false),
+            AccountMeta::new_readonly(ore_api::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: [
+            RelayInstruction::SwitchRelayer.to_vec(),
+            SwitchRelayerArgs {
+                commission: commission.to_le_bytes(),
+            }
+            .to_bytes()
+            .to_vec(),
+        ]
+        .concat(),
+    }
+}
+
+// Builds a configure_payout instruction.
+pub fn configure_payout(
+    signer: Pubkey,
+    seed_authority: Pubkey,
+    destination: Pubkey,
+    threshold: u64,
+) -> Instruction {
+    let escrow_pda = Pubkey::find_program_address(&[ESCROW, seed_authority.as_ref()], &crate::id());
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(signer, true),
+            AccountMeta::new(escrow_pda.0, false),
+            AccountMeta::new_readonly(destination, false),
+        ],
+        data: [
+            RelayInstruction::ConfigurePayout.to_vec(),
+            ConfigurePayoutArgs {
+                threshold: threshold.to_le_bytes(),
+            }
+            .to_bytes()
+            .to_vec(),
+        ]
+        .concat(),
+    }
+}
+
+// Builds a payout instruction.
+pub fn payout(
+    signer: Pubkey,
+    relayer: Pubkey,
+    seed_authority: Pubkey,
+    destination: Pubkey,
+    sol_fee: u64,
+) -> Instruction {
+    let (escrow_pda, _) =
+        Pubkey::find_program_address(&[ESCROW, seed_authority.as_ref()], &crate::id());
+    let (proof_pda, _) =
+        Pubkey::find_program_address(&[PROOF, escrow_pda.as_ref()], &ore_api::id());
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(signer, true),
+            AccountMeta::new_readonly(CONFIG_ADDRESS, false),
+            AccountMeta::new_readonly(relayer, false),
+            AccountMeta::new(escrow_pda, false),
+            AccountMeta::new(proof_pda, false),
+            AccountMeta::new(destination, false),
+            AccountMeta::new_readonly(ore_api::consts::TREASURY_ADDRESS, false),
+            AccountMeta::new(ore_api::consts::TREASURY_TOKENS_ADDRESS, false),
+            AccountMeta::new_readonly(ore_api::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(sysvar::instructions::id(), false),
+        ],
+        data: [
+            RelayInstruction::Payout.to_vec(),
+            PayoutArgs {
+                fee: sol_fee.to_le_bytes(),
+            }
+            .to_bytes()
+            .to_vec(),
+        ]
+        .concat(),
+    }
+}
+
+// Builds an update_miner instruction.
+// The change is scheduled, and applies once commission on the current hash is collected or
+// MINER_CHANGE_DELAY_SLOTS elapse. Send again with the same miner to apply a scheduled change.
+pub fn update_miner(signer: Pubkey, seed_authority: Pubkey, miner: Pubkey) -> Instruction {
+    let escrow_pda = Pubkey::find_program_address(&[ESCROW, seed_authority.as_ref()], &crate::id());
+    let proof_pda = Pubkey::find_program_address(&[PROOF, escrow_pda.0.as_ref()], &ore_api::id());
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(signer, true),
+            AccountMeta::new_readonly(CONFIG_ADDRESS, false),
             AccountMeta::new(escrow_pda.0, false),
             AccountMeta::new_readonly(miner, false),
             AccountMeta::new(proof_pda.0, false),
@@ -210,3 +677,298 @@ pub fn update_miner(signer: Pubkey, miner: Pubkey) -> Instruction {
         data: RelayInstruction::UpdateMiner.to_vec(),
     }
 }
+
+// Builds a register_relayer instruction.
+pub fn register_relayer(
+    signer: Pubkey,
+    miner: Pubkey,
+    beneficiary: Pubkey,
+    commission: u64,
+    bond: u64,
+) -> Instruction {
+    let relayer_pda = Pubkey::find_program_address(&[RELAYER, signer.as_ref()], &crate::id());
+    let relayer_vault =
+        spl_associated_token_account::get_associated_token_address(&relayer_pda.0, &MINT_ADDRESS);
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(signer, true),
+            AccountMeta::new_readonly(CONFIG_ADDRESS, false),
+            AccountMeta::new_readonly(miner, true),
+            AccountMeta::new_readonly(beneficiary, false),
+            AccountMeta::new(relayer_pda.0, false),
+            AccountMeta::new(relayer_vault, false),
+            AccountMeta::new_readonly(MINT_ADDRESS, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+        ],
+        data: [
+            RelayInstruction::RegisterRelayer.to_vec(),
+            RegisterRelayerArgs {
+                bump: relayer_pda.1,
+                commission: commission.to_le_bytes(),
+                bond: bond.to_le_bytes(),
+            }
+            .to_bytes()
+            .to_vec(),
+        ]
+        .concat(),
+    }
+}
+
+// Builds an update_relayer instruction.
+pub fn update_relayer(signer: Pubkey, beneficiary: Pubkey, commission: u64) -> Instruction {
+    let relayer_pda = Pubkey::find_program_address(&[RELAYER, signer.as_ref()], &crate::id());
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(signer, true),
+            AccountMeta::new_readonly(CONFIG_ADDRESS, false),
+            AccountMeta::new_readonly(beneficiary, false),
+            AccountMeta::new(relayer_pda.0, false),
+        ],
+        data: [
+            RelayInstruction::UpdateRelayer.to_vec(),
+            UpdateRelayerArgs {
+                commission: commission.to_le_bytes(),
+            }
+            .to_bytes()
+            .to_vec(),
+        ]
+        .concat(),
+    }
+}
+
+// Builds a deregister_relayer instruction.
+// The beneficiary receives any commission left in the relayer vault when it is closed.
+pub fn deregister_relayer(signer: Pubkey, beneficiary: Pubkey) -> Instruction {
+    let relayer_pda = Pubkey::find_program_address(&[RELAYER, signer.as_ref()], &crate::id());
+    let relayer_vault =
+        spl_associated_token_account::get_associated_token_address(&relayer_pda.0, &MINT_ADDRESS);
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(signer, true),
+            AccountMeta::new_readonly(CONFIG_ADDRESS, false),
+            AccountMeta::new(relayer_pda.0, false),
+            AccountMeta::new(beneficiary, false),
+            AccountMeta::new(relayer_vault, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: RelayInstruction::DeregisterRelayer.to_vec(),
+    }
+}
+
+// Builds a mine instruction.
+// Mines the escrowed proof and collects commission on the reward in one instruction. The
+// transaction must also include ore_api::instruction::auth for the proof.
+pub fn mine(
+    signer: Pubkey,
+    relayer: Pubkey,
+    seed_authority: Pubkey,
+    bus: Pubkey,
+    solution: Solution,
+    sol_fee: u64,
+) -> Instruction {
+    let (escrow_pda, _) =
+        Pubkey::find_program_address(&[ESCROW, seed_authority.as_ref()], &crate::id());
+    let (proof_pda, _) =
+        Pubkey::find_program_address(&[PROOF, escrow_pda.as_ref()], &ore_api::id());
+    let relayer_vault =
+        spl_associated_token_account::get_associated_token_address(&relayer, &MINT_ADDRESS);
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(signer, true),
+            AccountMeta::new_readonly(CONFIG_ADDRESS, false),
+            AccountMeta::new(relayer, false),
+            AccountMeta::new(escrow_pda, false),
+            AccountMeta::new(proof_pda, false),
+            AccountMeta::new(relayer_vault, false),
+            AccountMeta::new(bus, false),
+            AccountMeta::new_readonly(ORE_CONFIG_ADDRESS, false),
+            AccountMeta::new_readonly(ore_api::consts::TREASURY_ADDRESS, false),
+            AccountMeta::new(ore_api::consts::TREASURY_TOKENS_ADDRESS, false),
+            AccountMeta::new_readonly(sysvar::instructions::id(), false),
+            AccountMeta::new_readonly(sysvar::slot_hashes::id(), false),
+            AccountMeta::new_readonly(ore_api::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: [
+            RelayInstruction::Mine.to_vec(),
+            MineArgs {
+                digest: solution.d,
+                nonce: solution.n,
+                fee: sol_fee.to_le_bytes(),
+            }
+            .to_bytes()
+            .to_vec(),
+        ]
+        .concat(),
+    }
+}
+
+// Builds a withdraw_commission instruction.
+// Pays the commission accrued in the relayer vault to the beneficiary in one transfer.
+pub fn withdraw_commission(signer: Pubkey, beneficiary: Pubkey) -> Instruction {
+    let relayer_pda = Pubkey::find_program_address(&[RELAYER, signer.as_ref()], &crate::id());
+    let relayer_vault =
+        spl_associated_token_account::get_associated_token_address(&relayer_pda.0, &MINT_ADDRESS);
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(signer, true),
+            AccountMeta::new_readonly(CONFIG_ADDRESS, false),
+            AccountMeta::new(relayer_pda.0, false),
+            AccountMeta::new(beneficiary, false),
+            AccountMeta::new(relayer_vault, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: RelayInstruction::WithdrawCommission.to_vec(),
+    }
+}
+
+// Builds a slash_relayer instruction.
+// The evidence must be verified by an Ed25519 program instruction immediately preceding it.
+pub fn slash_relayer(signer: Pubkey, seed_authority: Pubkey, relayer: Pubkey) -> Instruction {
+    let escrow_pda = Pubkey::find_program_address(&[ESCROW, seed_authority.as_ref()], &crate::id());
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(signer, true),
+            AccountMeta::new_readonly(CONFIG_ADDRESS, false),
+            AccountMeta::new(escrow_pda.0, false),
+            AccountMeta::new(relayer, false),
+            AccountMeta::new_readonly(sysvar::instructions::id(), false),
+        ],
+        data: RelayInstruction::SlashRelayer.to_vec(),
+    }
+}
+
+// Builds a propose_authority instruction.
+// Proposing the default pubkey cancels a pending transfer.
+pub fn propose_authority(
+    signer: Pubkey,
+    seed_authority: Pubkey,
+    new_authority: Pubkey,
+) -> Instruction {
+    let escrow_pda = Pubkey::find_program_address(&[ESCROW, seed_authority.as_ref()], &crate::id());
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(signer, true),
+            AccountMeta::new(escrow_pda.0, false),
+            AccountMeta::new_readonly(new_authority, false),
+        ],
+        data: RelayInstruction::ProposeAuthority.to_vec(),
+    }
+}
+
+// Builds an accept_authority instruction.
+// The escrow keeps the address derived from the authority it was opened with.
+pub fn accept_authority(signer: Pubkey, seed_authority: Pubkey) -> Instruction {
+    let escrow_pda = Pubkey::find_program_address(&[ESCROW, seed_authority.as_ref()], &crate::id());
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(signer, true),
+            AccountMeta::new(escrow_pda.0, false),
+        ],
+        data: RelayInstruction::AcceptAuthority.to_vec(),
+    }
+}
+
+// Builds a revoke_relayer instruction.
+// The relayer may only be revoked once it has not collected from the escrow for
+// RELAYER_LIVENESS_SLOTS.
+pub fn revoke_relayer(
+    signer: Pubkey,
+    seed_authority: Pubkey,
+    relayer: Pubkey,
+    miner: Pubkey,
+) -> Instruction {
+    let escrow_pda = Pubkey::find_program_address(&[ESCROW, seed_authority.as_ref()], &crate::id());
+    let proof_pda = Pubkey::find_program_address(&[PROOF, escrow_pda.0.as_ref()], &ore_api::id());
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(signer, true),
+            AccountMeta::new_readonly(CONFIG_ADDRESS, false),
+            AccountMeta::new(escrow_pda.0, false),
+            AccountMeta::new_readonly(relayer, false),
+            AccountMeta::new_readonly(miner, false),
+            AccountMeta::new(proof_pda.0, false),
+            AccountMeta::new_readonly(ore_api::id(), false),
+        ],
+        data: RelayInstruction::RevokeRelayer.to_vec(),
+    }
+}
+
+// Builds an initialize instruction.
+pub fn initialize(signer: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(signer, true),
+            AccountMeta::new(CONFIG_ADDRESS, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: RelayInstruction::Initialize.to_vec(),
+    }
+}
+
+// Builds a set_admin instruction.
+pub fn set_admin(signer: Pubkey, new_admin: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(signer, true),
+            AccountMeta::new(CONFIG_ADDRESS, false),
+            AccountMeta::new_readonly(new_admin, false),
+        ],
+        data: RelayInstruction::SetAdmin.to_vec(),
+    }
+}
+
+// Builds a set_paused instruction.
+pub fn set_paused(signer: Pubkey, paused: bool) -> Instruction {
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(signer, true),
+            AccountMeta::new(CONFIG_ADDRESS, false),
+        ],
+        data: [
+            RelayInstruction::SetPaused.to_vec(),
+            SetPausedArgs {
+                paused: paused as u8,
+            }
+            .to_bytes()
+            .to_vec(),
+        ]
+        .concat(),
+    }
+}
+
+// Builds a set_relayer_paused instruction.
+pub fn set_relayer_paused(signer: Pubkey, relayer: Pubkey, paused: bool) -> Instruction {
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(signer, true),
+            AccountMeta::new_readonly(CONFIG_ADDRESS, false),
+            AccountMeta::new(relayer, false),
+        ],
+        data: [
+            RelayInstruction::SetRelayerPaused.to_vec(),
+            SetPausedArgs {
+                paused: paused as u8,
+            }
+            .to_bytes()
+            .to_vec(),
+        ]
+        .concat(),
+    }
+}
@@ -1,5 +1,8 @@
+#![allow(clippy::needless_lifetimes)]
+
 pub mod consts;
 pub mod error;
+pub mod event;
 pub mod instruction;
 pub mod loaders;
 pub mod state;
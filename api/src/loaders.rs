@@ -2,11 +2,48 @@ use ore_utils::AccountDeserialize;
 use solana_program::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey};
 
 pub use crate::state::*;
+use crate::{
+    consts::{CONFIG_ADDRESS, ESCROW_VERSION},
+    error::RelayError,
+};
 pub use ore_api::loaders::*;
 
 /// Errors if:
 /// - Owner is not relay program.
+/// - Address does not match the expected address.
 /// - Data is empty.
+/// - Account cannot be parsed to a config account.
+/// - Expected to be writable, but is not.
+pub fn load_relay_config<'a, 'info>(
+    info: &'a AccountInfo<'info>,
+    is_writable: bool,
+) -> Result<(), ProgramError> {
+    if info.owner.ne(&crate::id()) {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+
+    if info.key.ne(&CONFIG_ADDRESS) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    if info.data_is_empty() {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    let config_data = info.data.borrow();
+    let _ = Config::try_from_bytes(&config_data)?;
+
+    if is_writable && !info.is_writable {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(())
+}
+
+/// Errors if:
+/// - Owner is not relay program.
+/// - Data is empty.
+/// - Escrow layout is older than the current version.
 /// - Account cannot be parsed to a escrow account.
 /// - Escrow authority is not expected value.
 /// - Expected to be writable, but is not.
@@ -24,9 +61,12 @@ pub fn load_escrow<'a, 'info>(
     }
 
     let escrow_data = info.data.borrow();
+    if Escrow::version_of(&escrow_data)?.lt(&ESCROW_VERSION) {
+        return Err(RelayError::EscrowOutdated.into());
+    }
     let escrow = Escrow::try_from_bytes(&escrow_data)?;
 
-    if escrow.authority.ne(&authority) {
+    if escrow.authority.ne(authority) {
         return Err(ProgramError::InvalidAccountData);
     }
 
@@ -40,6 +80,7 @@ pub fn load_escrow<'a, 'info>(
 /// Errors if:
 /// - Owner is not relay program.
 /// - Data is empty.
+/// - Escrow layout is older than the current version.
 /// - Account cannot be parsed to a escrow account.
 /// - Expected to be writable, but is not.
 pub fn load_any_escrow<'a, 'info>(
@@ -55,6 +96,9 @@ pub fn load_any_escrow<'a, 'info>(
     }
 
     let escrow_data = info.data.borrow();
+    if Escrow::version_of(&escrow_data)?.lt(&ESCROW_VERSION) {
+        return Err(RelayError::EscrowOutdated.into());
+    }
     let _ = Escrow::try_from_bytes(&escrow_data)?;
 
     if is_writable && !info.is_writable {
@@ -63,3 +107,98 @@ pub fn load_any_escrow<'a, 'info>(
 
     Ok(())
 }
+
+/// Accepts escrow accounts of any layout version, for use during migrations.
+///
+/// Errors if:
+/// - Owner is not relay program.
+/// - Data is empty.
+/// - Account cannot be parsed to a escrow account of any version.
+/// - Escrow authority is not expected value.
+/// - Expected to be writable, but is not.
+pub fn load_escrow_any_version<'a, 'info>(
+    info: &'a AccountInfo<'info>,
+    authority: &Pubkey,
+    is_writable: bool,
+) -> Result<(), ProgramError> {
+    if info.owner.ne(&crate::id()) {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+
+    if info.data_is_empty() {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    let escrow_data = info.data.borrow();
+    let escrow = EscrowV1::try_from_bytes_prefix(&escrow_data)?;
+
+    if escrow.authority.ne(authority) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if is_writable && !info.is_writable {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(())
+}
+
+/// Errors if:
+/// - Owner is not relay program.
+/// - Data is empty.
+/// - Account cannot be parsed to a relayer account.
+/// - Relayer authority is not expected value.
+/// - Expected to be writable, but is not.
+pub fn load_relayer<'a, 'info>(
+    info: &'a AccountInfo<'info>,
+    authority: &Pubkey,
+    is_writable: bool,
+) -> Result<(), ProgramError> {
+    if info.owner.ne(&crate::id()) {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+
+    if info.data_is_empty() {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    let relayer_data = info.data.borrow();
+    let relayer = Relayer::try_from_bytes(&relayer_data)?;
+
+    if relayer.authority.ne(authority) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if is_writable && !info.is_writable {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(())
+}
+
+/// Errors if:
+/// - Owner is not relay program.
+/// - Data is empty.
+/// - Account cannot be parsed to a relayer account.
+/// - Expected to be writable, but is not.
+pub fn load_any_relayer<'a, 'info>(
+    info: &'a AccountInfo<'info>,
+    is_writable: bool,
+) -> Result<(), ProgramError> {
+    if info.owner.ne(&crate::id()) {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+
+    if info.data_is_empty() {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    let relayer_data = info.data.borrow();
+    let _ = Relayer::try_from_bytes(&relayer_data)?;
+
+    if is_writable && !info.is_writable {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,25 @@
+use bytemuck::{Pod, Zeroable};
+use ore_utils::{impl_account_from_bytes, impl_to_bytes, Discriminator};
+use solana_program::pubkey::Pubkey;
+
+use super::AccountDiscriminator;
+
+/// Config account
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct Config {
+    /// The signer authorized to administer the program.
+    pub admin: Pubkey,
+
+    /// Whether relayer-side instructions are halted (1) or not (0).
+    pub paused: u64,
+}
+
+impl Discriminator for Config {
+    fn discriminator() -> u8 {
+        AccountDiscriminator::Config.into()
+    }
+}
+
+impl_to_bytes!(Config);
+impl_account_from_bytes!(Config);
@@ -1,14 +1,22 @@
+use std::mem::size_of;
+
 use bytemuck::{Pod, Zeroable};
-use ore_utils::{impl_account_from_bytes, impl_to_bytes, Discriminator};
-use solana_program::pubkey::Pubkey;
+use ore_utils::{impl_account_from_bytes, impl_to_bytes, AccountDeserialize, Discriminator};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+
+use crate::consts::{DENOMINATOR_BPS, ESCROW_LEGACY_VERSION, RELAYER_LIVENESS_SLOTS};
 
 use super::AccountDiscriminator;
 
 /// Escrow account
+///
+/// New fields must be appended to the end of the struct and introduced with a bump of
+/// `ESCROW_VERSION`, so that older accounts can be upgraded in place by `MigrateEscrow`.
 #[repr(C)]
 #[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
 pub struct Escrow {
     /// The signer authorized to use this relay account.
+    /// May differ from the seed authority once the escrow has been transferred.
     pub authority: Pubkey,
 
     /// The bump used for signing CPIs.
@@ -17,8 +25,149 @@ pub struct Escrow {
     /// The last hash this relayer has collected commission on.
     pub last_hash: [u8; 32],
 
-    /// The last observed balance of the escrowed proof acount.
+    /// The last observed balance of the escrowed proof acount, net of commission.
+    /// Rewards mined above this balance are owed commission and cannot be claimed until collected.
     pub last_balance: u64,
+
+    /// The layout version of this account.
+    pub version: u64,
+
+    /// The relayer account this escrow has selected to mine on its behalf.
+    pub relayer: Pubkey,
+
+    /// The commission rate agreed with the relayer, in basis points of mined rewards.
+    pub commission: u64,
+
+    /// The maximum SOL fee, in lamports, the relayer may be reimbursed per collect.
+    pub max_fee_per_collect: u64,
+
+    /// The maximum total SOL fee, in lamports, the relayer may be reimbursed per epoch.
+    pub epoch_fee_budget: u64,
+
+    /// The epoch in which fees were last reimbursed.
+    pub fee_epoch: u64,
+
+    /// The total SOL fee, in lamports, reimbursed during the fee epoch.
+    pub fee_epoch_spent: u64,
+
+    /// Whether the relayer restakes the net reward of each collect into the proof, if nonzero.
+    pub compound: u64,
+
+    /// The token account the relayer may pay out the collected balance to.
+    pub payout_destination: Pubkey,
+
+    /// The collected balance at which the relayer may trigger a payout. Payouts are disabled while zero.
+    pub payout_threshold: u64,
+
+    /// The authority whose address seeds the escrow PDA. Fixed at open, so the escrow can still
+    /// sign CPIs after its authority is transferred.
+    pub seed_authority: Pubkey,
+
+    /// The authority proposed to take over this escrow, or the default pubkey if none.
+    pub pending_authority: Pubkey,
+
+    /// The rent, in lamports, sponsored by the rent payer at open, and repaid to it when the escrow
+    /// closes.
+    ///
+    /// The debt is repaid in lamports out of the rent reclaimed from the escrow, proof and token
+    /// accounts, not recouped from commission. Rent is paid in SOL and commission in ORE, so
+    /// recouping from commission would need a price, and would only repay a sponsor that is also
+    /// the relayer. The trade-off is that the sponsor is repaid only once the escrow closes.
+    pub rent_debt: u64,
+
+    /// Lifetime statistics of this escrow.
+    pub stats: EscrowStats,
+
+    /// The miner scheduled to replace the current miner of the proof, or the default pubkey if none.
+    pub pending_miner: Pubkey,
+
+    /// The slot from which the pending miner may replace the current miner, even if commission on
+    /// the current hash has not been collected.
+    pub pending_miner_slot: u64,
+
+    /// The largest overcharge, in lamports, slashed from the current relayer into this escrow.
+    pub slashed: u64,
+
+    /// The payer owed the rent debt, or the default pubkey if none.
+    pub rent_payer: Pubkey,
+
+    /// The slot at which the escrow was bound to its current relayer.
+    pub relayer_slot: u64,
+}
+
+/// Lifetime statistics of an escrow account, maintained by each processor.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct EscrowStats {
+    /// The total commission settled to relayers.
+    pub total_commission: u64,
+
+    /// The total SOL fees, in lamports, reimbursed to relayers.
+    pub total_reimbursed: u64,
+
+    /// The number of collects.
+    pub collects: u64,
+
+    /// The total ORE staked into the proof.
+    pub total_staked: u64,
+
+    /// The total ORE claimed or paid out of the proof.
+    pub total_claimed: u64,
+
+    /// The slot of the first collect, or 0 if none.
+    pub first_collect_slot: u64,
+
+    /// The slot of the latest collect, or 0 if none.
+    pub last_collect_slot: u64,
+}
+
+impl Escrow {
+    /// Returns the lifetime statistics of serialized escrow account data, or `None` if the
+    /// escrow is legacy.
+    pub fn stats_of(data: &[u8]) -> Result<Option<EscrowStats>, ProgramError> {
+        if Self::version_of(data)?.eq(&ESCROW_LEGACY_VERSION) {
+            return Ok(None);
+        }
+        let escrow = Self::try_from_bytes(data)?;
+        Ok(Some(escrow.stats))
+    }
+
+    /// Returns the layout version of serialized escrow account data.
+    pub fn version_of(data: &[u8]) -> Result<u64, ProgramError> {
+        let header_len = 8 + size_of::<EscrowV1>();
+        if data.len().lt(&header_len) || data[0].ne(&Self::discriminator()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if data.len().eq(&header_len) {
+            return Ok(ESCROW_LEGACY_VERSION);
+        }
+        let version = data
+            .get(header_len..header_len + 8)
+            .ok_or(ProgramError::InvalidAccountData)?;
+        Ok(u64::from_le_bytes(version.try_into().unwrap()))
+    }
+
+    /// Returns the slot of the latest collect by the relayer, or the slot the escrow was bound to
+    /// it if it has not collected since.
+    pub fn last_active_slot(&self) -> u64 {
+        self.stats.last_collect_slot.max(self.relayer_slot)
+    }
+
+    /// Returns whether the relayer has not collected from this escrow for longer than the
+    /// liveness period, and may be revoked.
+    pub fn is_stale(&self, slot: u64) -> bool {
+        slot.saturating_sub(self.last_active_slot())
+            .gt(&RELAYER_LIVENESS_SLOTS)
+    }
+
+    /// Returns the commission owed to the relayer on the given reward.
+    pub fn commission_on(&self, reward: u64) -> Option<u64> {
+        (reward as u128)
+            .checked_mul(self.commission as u128)?
+            .checked_div(DENOMINATOR_BPS as u128)?
+            .try_into()
+            .ok()
+    }
 }
 
 impl Default for Escrow {
@@ -28,6 +177,25 @@ impl Default for Escrow {
             bump: 0,
             last_hash: [0; 32],
             last_balance: 0,
+            version: 0,
+            relayer: Pubkey::new_from_array([0; 32]),
+            commission: 0,
+            max_fee_per_collect: 0,
+            epoch_fee_budget: 0,
+            fee_epoch: 0,
+            fee_epoch_spent: 0,
+            compound: 0,
+            payout_destination: Pubkey::new_from_array([0; 32]),
+            payout_threshold: 0,
+            seed_authority: Pubkey::new_from_array([0; 32]),
+            pending_authority: Pubkey::new_from_array([0; 32]),
+            rent_debt: 0,
+            stats: EscrowStats::default(),
+            pending_miner: Pubkey::new_from_array([0; 32]),
+            pending_miner_slot: 0,
+            slashed: 0,
+            rent_payer: Pubkey::new_from_array([0; 32]),
+            relayer_slot: 0,
         }
     }
 }
@@ -40,3 +208,96 @@ impl Discriminator for Escrow {
 
 impl_to_bytes!(Escrow);
 impl_account_from_bytes!(Escrow);
+
+/// Escrow account layout prior to versioning.
+/// Every later layout begins with these fields, so they can be read from escrows of any version.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct EscrowV1 {
+    /// The signer authorized to use this relay account.
+    pub authority: Pubkey,
+
+    /// The bump used for signing CPIs.
+    pub bump: u64,
+
+    /// The last hash this relayer has collected commission on.
+    pub last_hash: [u8; 32],
+
+    /// The last observed balance of the escrowed proof acount.
+    pub last_balance: u64,
+}
+
+impl EscrowV1 {
+    /// Parses the fields shared by every escrow layout version.
+    pub fn try_from_bytes_prefix(data: &[u8]) -> Result<&Self, ProgramError> {
+        if data.is_empty() || data[0].ne(&Self::discriminator()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let header = data
+            .get(8..8 + size_of::<Self>())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        bytemuck::try_from_bytes::<Self>(header).or(Err(ProgramError::InvalidAccountData))
+    }
+}
+
+impl Discriminator for EscrowV1 {
+    fn discriminator() -> u8 {
+        AccountDiscriminator::Escrow.into()
+    }
+}
+
+impl_to_bytes!(EscrowV1);
+impl_account_from_bytes!(EscrowV1);
+
+#[cfg(test)]
+mod tests {
+    use crate::consts::ESCROW_VERSION;
+
+    use super::*;
+
+    fn escrow_data(escrow: Escrow) -> Vec<u8> {
+        let mut data = vec![0; 8 + size_of::<Escrow>()];
+        data[0] = Escrow::discriminator();
+        *Escrow::try_from_bytes_mut(&mut data).unwrap() = escrow;
+        data
+    }
+
+    #[test]
+    fn test_version_of() {
+        let authority = Pubkey::new_unique();
+        let data = escrow_data(Escrow {
+            authority,
+            version: ESCROW_VERSION,
+            ..Default::default()
+        });
+        assert_eq!(Escrow::version_of(&data), Ok(ESCROW_VERSION));
+
+        // Legacy escrows hold no version
+        let legacy = &data[..8 + size_of::<EscrowV1>()];
+        assert_eq!(Escrow::version_of(legacy), Ok(ESCROW_LEGACY_VERSION));
+
+        // Other accounts are rejected
+        assert!(Escrow::version_of(&data[..8 + size_of::<EscrowV1>() - 1]).is_err());
+        let mut other = data.clone();
+        other[0] = AccountDiscriminator::Relayer.into();
+        assert!(Escrow::version_of(&other).is_err());
+    }
+
+    #[test]
+    fn test_stats_of() {
+        let stats = EscrowStats {
+            collects: 2,
+            ..Default::default()
+        };
+        let data = escrow_data(Escrow {
+            version: ESCROW_VERSION,
+            stats,
+            ..Default::default()
+        });
+        assert_eq!(Escrow::stats_of(&data), Ok(Some(stats)));
+
+        // Legacy escrows hold none
+        let legacy = &data[..8 + size_of::<EscrowV1>()];
+        assert_eq!(Escrow::stats_of(legacy), Ok(None));
+    }
+}
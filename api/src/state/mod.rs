@@ -1,6 +1,10 @@
+mod config;
 mod escrow;
+mod relayer;
 
+pub use config::*;
 pub use escrow::*;
+pub use relayer::*;
 
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 
@@ -8,4 +12,6 @@ use num_enum::{IntoPrimitive, TryFromPrimitive};
 #[derive(Clone, Copy, Debug, Eq, PartialEq, IntoPrimitive, TryFromPrimitive)]
 pub enum AccountDiscriminator {
     Escrow = 100,
+    Relayer = 101,
+    Config = 102,
 }
@@ -0,0 +1,59 @@
+use bytemuck::{Pod, Zeroable};
+use ore_utils::{impl_account_from_bytes, impl_to_bytes, Discriminator};
+use solana_program::pubkey::Pubkey;
+
+use super::AccountDiscriminator;
+
+/// Relayer account
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct Relayer {
+    /// The signer authorized to manage this relayer account.
+    pub authority: Pubkey,
+
+    /// The bump of the relayer account PDA.
+    pub bump: u64,
+
+    /// The keypair which submits hashes and collects commission on behalf of escrows.
+    /// Fixed at registration.
+    pub miner: Pubkey,
+
+    /// The commission rate offered to new escrows, in basis points of mined rewards.
+    pub commission: u64,
+
+    /// The token account which receives commission withdrawn from the relayer vault.
+    pub beneficiary: Pubkey,
+
+    /// Whether this relayer is halted by an admin emergency stop (1) or not (0).
+    /// Only the admin may clear an admin stop.
+    pub admin_paused: u64,
+
+    /// Whether this relayer is halted by its own authority (1) or not (0).
+    pub self_paused: u64,
+
+    /// The SOL bond, in lamports, held in this account and slashable by escrows.
+    pub bond: u64,
+
+    /// The slot after which the relayer may deregister, or 0 if deregistration is not requested.
+    pub unbonding_slot: u64,
+
+    /// The commission held in the relayer vault, the associated token account of this relayer,
+    /// and not yet withdrawn.
+    pub accrued_commission: u64,
+}
+
+impl Relayer {
+    /// Returns whether this relayer is halted by the admin or its own authority.
+    pub fn is_paused(&self) -> bool {
+        self.admin_paused.gt(&0) || self.self_paused.gt(&0)
+    }
+}
+
+impl Discriminator for Relayer {
+    fn discriminator() -> u8 {
+        AccountDiscriminator::Relayer.into()
+    }
+}
+
+impl_to_bytes!(Relayer);
+impl_account_from_bytes!(Relayer);
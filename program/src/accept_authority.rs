@@ -0,0 +1,49 @@
+use ore_relayer_api::{error::RelayError, event::AcceptAuthorityEvent, loaders::*};
+use ore_utils::AccountDeserialize;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+/// Accepts a pending authority transfer of an escrow account. The escrow PDA remains seeded by
+/// the authority it was opened with. Payout settings and any pending miner change of the previous
+/// authority are cleared.
+pub fn process_accept_authority<'a, 'info>(
+    accounts: &'a [AccountInfo<'info>],
+    _data: &[u8],
+) -> ProgramResult {
+    // Load accounts
+    let [signer, escrow_info] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    load_signer(signer)?;
+    load_any_escrow(escrow_info, true)?;
+
+    // Error if the signer is not the pending authority
+    let mut escrow_data = escrow_info.data.borrow_mut();
+    let escrow = Escrow::try_from_bytes_mut(&mut escrow_data)?;
+    if escrow.pending_authority.ne(signer.key) {
+        return Err(RelayError::InvalidPendingAuthority.into());
+    }
+
+    // Transfer authority
+    let previous_authority = escrow.authority;
+    escrow.authority = *signer.key;
+    escrow.pending_authority = Pubkey::new_from_array([0; 32]);
+
+    // Clear the settings of the previous authority
+    escrow.payout_destination = Pubkey::new_from_array([0; 32]);
+    escrow.payout_threshold = 0;
+    escrow.pending_miner = Pubkey::new_from_array([0; 32]);
+    escrow.pending_miner_slot = 0;
+
+    // Log event
+    AcceptAuthorityEvent {
+        escrow: *escrow_info.key,
+        previous_authority,
+        authority: *signer.key,
+    }
+    .log();
+
+    Ok(())
+}
@@ -1,5 +1,7 @@
 use ore_api::consts::MINT_ADDRESS;
-use ore_relayer_api::{consts::*, instruction::ClaimArgs, loaders::*};
+use ore_relayer_api::{
+    consts::*, error::RelayError, event::ClaimEvent, instruction::ClaimArgs, loaders::*,
+};
 use ore_utils::AccountDeserialize;
 use solana_program::{
     account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
@@ -26,13 +28,20 @@ pub fn process_claim<'a, 'info>(accounts: &'a [AccountInfo<'info>], data: &[u8])
     load_program(ore_program, ore_api::id())?;
     load_program(token_program, spl_token::id())?;
 
-    // Decrement last balance
+    // Decrement last balance. Rewards which have not been collected yet are owed commission,
+    // so users may only claim up to the balance observed at the last collect.
     let mut escrow_data = escrow_info.data.borrow_mut();
     let escrow = Escrow::try_from_bytes_mut(&mut escrow_data)?;
-    escrow.last_balance -= amount;
+    escrow.last_balance = escrow
+        .last_balance
+        .checked_sub(amount)
+        .ok_or(RelayError::ClaimExceedsCollected)?;
+    escrow.stats.total_claimed = escrow.stats.total_claimed.saturating_add(amount);
 
     // Claim stake to beneficiary
+    let seed_authority = escrow.seed_authority;
     let escrow_bump = escrow.bump as u8;
+    let last_balance = escrow.last_balance;
     drop(escrow_data);
     solana_program::program::invoke_signed(
         &ore_api::instruction::claim(*escrow_info.key, *beneficiary_info.key, amount),
@@ -44,8 +53,17 @@ pub fn process_claim<'a, 'info>(accounts: &'a [AccountInfo<'info>], data: &[u8])
             treasury_tokens_info.clone(),
             token_program.clone(),
         ],
-        &[&[ESCROW, signer.key.as_ref(), &[escrow_bump]]],
+        &[&[ESCROW, seed_authority.as_ref(), &[escrow_bump]]],
     )?;
 
+    // Log event
+    ClaimEvent {
+        escrow: *escrow_info.key,
+        beneficiary: *beneficiary_info.key,
+        amount,
+        last_balance,
+    }
+    .log();
+
     Ok(())
 }
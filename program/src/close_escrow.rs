@@ -1,30 +1,130 @@
-use ore_relayer_api::{consts::*, loaders::*};
-use ore_utils::AccountDeserialize;
+use ore_api::{consts::MINT_ADDRESS, state::Proof};
+use ore_relayer_api::{consts::*, error::RelayError, event::CloseEscrowEvent, loaders::*};
+use ore_utils::{spl::transfer_signed, AccountDeserialize};
 use solana_program::{
     account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
-    system_program,
+    program_pack::Pack, pubkey::Pubkey, system_program,
 };
 
-/// Closes an escrow account.
+use crate::utils::settle_commission;
+
+/// Closes an escrow account, along with its proof and token accounts. Commission owed to the
+/// relayer is settled into the relayer vault first, and the rent debt is repaid to the rent payer from the
+/// lamports of the closed accounts.
 pub fn process_close_escrow<'a, 'info>(
     accounts: &'a [AccountInfo<'info>],
     _data: &[u8],
 ) -> ProgramResult {
     // Load accounts.
-    let [signer, escrow_info, proof_info, ore_program, system_program] = accounts else {
+    let [signer, beneficiary_info, relayer_info, vault_info, rent_payer_info, escrow_info, escrow_tokens_info, proof_info, treasury_info, treasury_tokens_info, ore_program, system_program, token_program] =
+        accounts
+    else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
     load_signer(signer)?;
-    load_escrow(escrow_info, signer.key, true)?;
+    load_token_account(beneficiary_info, None, &MINT_ADDRESS, true)?;
+    load_any(rent_payer_info, true)?;
+    load_escrow_any_version(escrow_info, signer.key, true)?;
+    load_token_account(
+        escrow_tokens_info,
+        Some(escrow_info.key),
+        &MINT_ADDRESS,
+        true,
+    )?;
     load_proof(proof_info, escrow_info.key, true)?;
+    load_treasury(treasury_info, false)?;
+    load_treasury_tokens(treasury_tokens_info, true)?;
     load_program(ore_program, ore_api::id())?;
     load_program(system_program, system_program::id())?;
+    load_program(token_program, spl_token::id())?;
 
-    // Close the proof account.
-    let escrow_data = escrow_info.data.borrow();
-    let escrow = Escrow::try_from_bytes(&escrow_data)?;
-    let escrow_bump = escrow.bump as u8;
+    // Settle commission on rewards mined since the last collect, so the user can exit without
+    // waiting on the relayer. Legacy escrows must be migrated first if commission is owed.
+    let mut escrow_data = escrow_info.data.borrow_mut();
+    let proof_data = proof_info.data.borrow();
+    let proof = Proof::try_from_bytes(&proof_data)?;
+    let (commission, seed_authority, rent_debt) =
+        if Escrow::version_of(&escrow_data)?.eq(&ESCROW_LEGACY_VERSION) {
+            // Legacy escrows cannot have been transferred, so their authority seeds the PDA. They
+            // owe no commission or rent debt beyond the uncollected reward.
+            let escrow = EscrowV1::try_from_bytes_prefix(&escrow_data)?;
+            if escrow.last_hash.ne(&proof.last_hash) {
+                return Err(RelayError::EscrowOutdated.into());
+            }
+            (0, escrow.authority, 0)
+        } else {
+            let escrow = Escrow::try_from_bytes_mut(&mut escrow_data)?;
+            if escrow.relayer.ne(relayer_info.key) {
+                return Err(RelayError::UnauthorizedRelayer.into());
+            }
+            if escrow.rent_debt.gt(&0) && escrow.rent_payer.ne(rent_payer_info.key) {
+                return Err(RelayError::InvalidRentPayer.into());
+            }
+            let commission = if escrow.last_hash.ne(&proof.last_hash) {
+                settle_commission(escrow, proof)?.1
+            } else {
+                0
+            };
+
+            // Accrue commission to the relayer vault. If the escrow was unbound by a revoke or
+            // the relayer has deregistered, it is returned to the user.
+            let unbound = escrow.relayer.eq(&Pubkey::new_from_array([0; 32]));
+            let commission = if unbound || relayer_info.data_is_empty() {
+                0
+            } else {
+                load_any_relayer(relayer_info, true)?;
+                load_token_account(vault_info, Some(relayer_info.key), &MINT_ADDRESS, true)?;
+                let mut relayer_data = relayer_info.data.borrow_mut();
+                let relayer = Relayer::try_from_bytes_mut(&mut relayer_data)?;
+                relayer.accrued_commission = relayer
+                    .accrued_commission
+                    .checked_add(commission)
+                    .ok_or(RelayError::BalanceOverflow)?;
+                commission
+            };
+            (commission, escrow.seed_authority, escrow.rent_debt)
+        };
+    let escrow_bump = EscrowV1::try_from_bytes_prefix(&escrow_data)?.bump as u8;
+    let proof_balance = proof
+        .balance
+        .checked_sub(commission)
+        .ok_or(RelayError::BalanceUnderflow)?;
     drop(escrow_data);
+    drop(proof_data);
+
+    // Claim commission to the relayer vault
+    if commission.gt(&0) {
+        solana_program::program::invoke_signed(
+            &ore_api::instruction::claim(*escrow_info.key, *vault_info.key, commission),
+            &[
+                escrow_info.clone(),
+                vault_info.clone(),
+                proof_info.clone(),
+                treasury_info.clone(),
+                treasury_tokens_info.clone(),
+                token_program.clone(),
+            ],
+            &[&[ESCROW, seed_authority.as_ref(), &[escrow_bump]]],
+        )?;
+    }
+
+    // Claim remaining proof balance to beneficiary
+    if proof_balance.gt(&0) {
+        solana_program::program::invoke_signed(
+            &ore_api::instruction::claim(*escrow_info.key, *beneficiary_info.key, proof_balance),
+            &[
+                escrow_info.clone(),
+                beneficiary_info.clone(),
+                proof_info.clone(),
+                treasury_info.clone(),
+                treasury_tokens_info.clone(),
+                token_program.clone(),
+            ],
+            &[&[ESCROW, seed_authority.as_ref(), &[escrow_bump]]],
+        )?;
+    }
+
+    // Close the proof account.
     solana_program::program::invoke_signed(
         &ore_api::instruction::close(*escrow_info.key),
         &[
@@ -32,15 +132,61 @@ pub fn process_close_escrow<'a, 'info>(
             proof_info.clone(),
             system_program.clone(),
         ],
-        &[&[ESCROW, signer.key.as_ref(), &[escrow_bump]]],
+        &[&[ESCROW, seed_authority.as_ref(), &[escrow_bump]]],
+    )?;
+
+    // Sweep idle tokens to beneficiary
+    let escrow_tokens_data = escrow_tokens_info.data.borrow();
+    let escrow_tokens = spl_token::state::Account::unpack(&escrow_tokens_data)?;
+    let idle_balance = escrow_tokens.amount;
+    drop(escrow_tokens_data);
+    if idle_balance.gt(&0) {
+        transfer_signed(
+            escrow_info,
+            escrow_tokens_info,
+            beneficiary_info,
+            token_program,
+            idle_balance,
+            &[&[ESCROW, seed_authority.as_ref(), &[escrow_bump]]],
+        )?;
+    }
+
+    // Close the escrow token account, returning rent to the escrow
+    solana_program::program::invoke_signed(
+        &spl_token::instruction::close_account(
+            &spl_token::id(),
+            escrow_tokens_info.key,
+            escrow_info.key,
+            escrow_info.key,
+            &[escrow_info.key],
+        )?,
+        &[
+            token_program.clone(),
+            escrow_tokens_info.clone(),
+            escrow_info.clone(),
+        ],
+        &[&[ESCROW, seed_authority.as_ref(), &[escrow_bump]]],
     )?;
 
     // Realloc data to zero
     escrow_info.realloc(0, true)?;
 
-    // Send lamports to signer
-    **signer.lamports.borrow_mut() += escrow_info.lamports();
+    // Repay the rent debt to the rent payer, and send remaining lamports to signer
+    let lamports = escrow_info.lamports();
+    let repaid = rent_debt.min(lamports);
     **escrow_info.lamports.borrow_mut() = 0;
+    **rent_payer_info.lamports.borrow_mut() += repaid;
+    **signer.lamports.borrow_mut() += lamports - repaid;
+
+    // Log event
+    CloseEscrowEvent {
+        escrow: *escrow_info.key,
+        authority: *signer.key,
+        claimed: proof_balance,
+        swept: idle_balance,
+        commission,
+    }
+    .log();
 
     Ok(())
 }
@@ -1,11 +1,21 @@
 use ore_api::{consts::MINT_ADDRESS, state::Proof};
-use ore_relayer_api::{consts::*, error::RelayError, instruction::CollectArgs, loaders::*};
+use ore_relayer_api::{
+    consts::*, error::RelayError, event::CollectEvent, instruction::CollectArgs, loaders::*,
+};
 use ore_utils::AccountDeserialize;
 use solana_program::{
-    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    sysvar::{self, Sysvar},
+};
+
+use crate::utils::{
+    assert_active, assert_fee_covered, claim_commission, reimburse_fee, settle_commission,
 };
 
-/// Collects commission from a miner.
+/// Collects commission from a miner into the relayer vault.
 pub fn process_collect<'a, 'info>(
     accounts: &'a [AccountInfo<'info>],
     data: &[u8],
@@ -15,62 +25,139 @@ pub fn process_collect<'a, 'info>(
     let fee = u64::from_le_bytes(args.fee);
 
     // Load accounts.
-    let [signer, beneficiary_info, escrow_info, proof_info, treasury_info, treasury_tokens_info, ore_program, token_program] =
+    let [signer, config_info, relayer_info, escrow_info, proof_info, vault_info, treasury_info, treasury_tokens_info, ore_program, token_program, instructions_sysvar] =
         accounts
     else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
     load_signer(signer)?;
-    load_token_account(beneficiary_info, None, &MINT_ADDRESS, true)?;
+    load_relay_config(config_info, false)?;
+    load_any_relayer(relayer_info, true)?;
     load_any_escrow(escrow_info, true)?;
     load_proof(proof_info, escrow_info.key, true)?;
+    load_token_account(vault_info, Some(relayer_info.key), &MINT_ADDRESS, true)?;
     load_treasury(treasury_info, false)?;
     load_treasury_tokens(treasury_tokens_info, true)?;
     load_program(ore_program, ore_api::id())?;
     load_program(token_program, spl_token::id())?;
+    load_sysvar(instructions_sysvar, sysvar::instructions::id())?;
 
-    // Verify signer
-    if signer.key.ne(&MINER_PUBKEY) {
-        return Err(RelayError::Dummy.into());
-    }
+    // Error if the program is paused
+    assert_active(config_info)?;
+
+    // Error if fees are claimed through CPI, or exceed the cost of the transaction
+    assert_fee_covered(instructions_sysvar)?;
+
+    // Verify signer is the relayer miner
+    let mut relayer_data = relayer_info.data.borrow_mut();
+    let relayer = Relayer::try_from_bytes_mut(&mut relayer_data)?;
+    assert_collector(signer, relayer)?;
 
     // Error if the last hash is the same (don't allow double collections)
-    let mut escrow_data = escrow_info.data.borrow_mut();
-    let escrow = Escrow::try_from_bytes_mut(&mut escrow_data)?;
+    let slot = Clock::get()?.slot;
+    let Some(commission) = collect_escrow(
+        signer,
+        relayer_info,
+        relayer,
+        escrow_info,
+        proof_info,
+        fee,
+        slot,
+    )?
+    else {
+        return Err(RelayError::DuplicateCollect.into());
+    };
+    drop(relayer_data);
+
+    // Claim commission into the relayer vault
+    claim_commission(
+        escrow_info,
+        proof_info,
+        vault_info,
+        treasury_info,
+        treasury_tokens_info,
+        token_program,
+        commission,
+    )?;
+
+    Ok(())
+}
+
+/// Errors if the signer may not collect on behalf of the relayer.
+pub fn assert_collector(signer: &AccountInfo, relayer: &Relayer) -> ProgramResult {
+    if signer.key.ne(&relayer.miner) {
+        return Err(RelayError::UnauthorizedRelayer.into());
+    }
+    if relayer.is_paused() || relayer.unbonding_slot.gt(&0) {
+        return Err(RelayError::RelayerPaused.into());
+    }
+    if relayer.bond.lt(&MIN_RELAYER_BOND) {
+        return Err(RelayError::InsufficientBond.into());
+    }
+    Ok(())
+}
+
+/// Collects commission from a single escrow, and reimburses the signer for the fee. Returns the
+/// commission for the caller to claim into the relayer vault, or None without collecting if the
+/// proof has not mined a new hash since the last collect.
+pub fn collect_escrow<'info>(
+    signer: &AccountInfo<'info>,
+    relayer_info: &AccountInfo<'info>,
+    relayer: &mut Relayer,
+    escrow_info: &AccountInfo<'info>,
+    proof_info: &AccountInfo<'info>,
+    fee: u64,
+    slot: u64,
+) -> Result<Option<u64>, ProgramError> {
+    // Verify the proof is mined by the relayer
     let proof_data = proof_info.data.borrow();
     let proof = Proof::try_from_bytes(&proof_data)?;
+    if proof.miner.ne(&relayer.miner) {
+        return Err(RelayError::InvalidMiner.into());
+    }
+
+    // Verify the relayer is the one selected by the escrow
+    let mut escrow_data = escrow_info.data.borrow_mut();
+    let escrow = Escrow::try_from_bytes_mut(&mut escrow_data)?;
+    if escrow.relayer.ne(relayer_info.key) {
+        return Err(RelayError::UnauthorizedRelayer.into());
+    }
+
+    // Skip if the last hash is the same
     if escrow.last_hash.eq(&proof.last_hash) {
-        return Err(RelayError::Dummy.into());
+        return Ok(None);
     }
 
-    // Assert sufficient miner reward
-    let sufficient_miner_reward = proof.balance.gt(&(escrow.last_balance + COMMISSION));
-    // Increment last balance & hash
-    escrow.last_balance = proof.balance;
-    escrow.last_hash = proof.last_hash;
-    // Claim commission
-    if sufficient_miner_reward {
-        let escrow_authority = escrow.authority;
-        let escrow_bump = escrow.bump as u8;
-        drop(escrow_data);
-        drop(proof_data);
-        solana_program::program::invoke_signed(
-            &ore_api::instruction::claim(*escrow_info.key, *beneficiary_info.key, COMMISSION),
-            &[
-                escrow_info.clone(),
-                beneficiary_info.clone(),
-                proof_info.clone(),
-                treasury_info.clone(),
-                treasury_tokens_info.clone(),
-                token_program.clone(),
-            ],
-            &[&[ESCROW, escrow_authority.as_ref(), &[escrow_bump]]],
-        )?;
+    // Accrue commission on the reward mined since the last collect. It is held in the relayer
+    // vault, and paid to the beneficiary in batches by WithdrawCommission.
+    let (reward, commission) = settle_commission(escrow, proof)?;
+    relayer.accrued_commission = relayer
+        .accrued_commission
+        .checked_add(commission)
+        .ok_or(RelayError::BalanceOverflow)?;
+    if escrow.stats.collects.eq(&0) {
+        escrow.stats.first_collect_slot = slot;
     }
+    escrow.stats.collects = escrow.stats.collects.saturating_add(1);
+    escrow.stats.last_collect_slot = slot;
+    let last_hash = escrow.last_hash;
+    let last_balance = escrow.last_balance;
+    drop(proof_data);
 
     // Send transaction fee to miner
-    **signer.lamports.borrow_mut() += fee;
-    **escrow_info.lamports.borrow_mut() -= fee;
+    reimburse_fee(escrow, escrow_info, signer, fee)?;
 
-    Ok(())
+    // Log event
+    CollectEvent {
+        escrow: *escrow_info.key,
+        relayer: *relayer_info.key,
+        reward,
+        commission,
+        fee,
+        last_hash,
+        last_balance,
+    }
+    .log();
+
+    Ok(Some(commission))
 }
@@ -0,0 +1,88 @@
+use ore_api::consts::MINT_ADDRESS;
+use ore_relayer_api::{instruction::CollectManyArgs, loaders::*};
+use ore_utils::AccountDeserialize;
+use solana_program::{
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    sysvar::{self, Sysvar},
+};
+
+use crate::{
+    collect::{assert_collector, collect_escrow},
+    utils::{assert_active, assert_fee_covered, claim_commission},
+};
+
+/// Collects commission from a batch of escrows, with the same rules as Collect. Escrows are
+/// passed as trailing pairs of escrow and proof accounts. Escrows whose proof has not mined a
+/// new hash since the last collect are skipped, and are not charged the fee.
+pub fn process_collect_many<'a, 'info>(
+    accounts: &'a [AccountInfo<'info>],
+    data: &[u8],
+) -> ProgramResult {
+    // Parse args
+    let args = CollectManyArgs::try_from_bytes(data)?;
+    let fee = u64::from_le_bytes(args.fee);
+
+    // Load accounts
+    let [signer, config_info, relayer_info, vault_info, treasury_info, treasury_tokens_info, ore_program, token_program, instructions_sysvar, escrow_infos @ ..] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    if escrow_infos.is_empty() || escrow_infos.len() % 2 != 0 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    load_signer(signer)?;
+    load_relay_config(config_info, false)?;
+    load_any_relayer(relayer_info, true)?;
+    load_token_account(vault_info, Some(relayer_info.key), &MINT_ADDRESS, true)?;
+    load_treasury(treasury_info, false)?;
+    load_treasury_tokens(treasury_tokens_info, true)?;
+    load_program(ore_program, ore_api::id())?;
+    load_program(token_program, spl_token::id())?;
+    load_sysvar(instructions_sysvar, sysvar::instructions::id())?;
+
+    // Error if the program is paused
+    assert_active(config_info)?;
+
+    // Error if fees are claimed through CPI, or exceed the cost of the transaction
+    assert_fee_covered(instructions_sysvar)?;
+
+    // Verify signer is the relayer miner
+    let mut relayer_data = relayer_info.data.borrow_mut();
+    let relayer = Relayer::try_from_bytes_mut(&mut relayer_data)?;
+    assert_collector(signer, relayer)?;
+
+    // Collect from each escrow
+    let slot = Clock::get()?.slot;
+    for pair in escrow_infos.chunks(2) {
+        let [escrow_info, proof_info] = pair else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        load_any_escrow(escrow_info, true)?;
+        load_proof(proof_info, escrow_info.key, true)?;
+        if let Some(commission) = collect_escrow(
+            signer,
+            relayer_info,
+            relayer,
+            escrow_info,
+            proof_info,
+            fee,
+            slot,
+        )? {
+            claim_commission(
+                escrow_info,
+                proof_info,
+                vault_info,
+                treasury_info,
+                treasury_tokens_info,
+                token_program,
+                commission,
+            )?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,149 @@
+use ore_api::consts::MINT_ADDRESS;
+use ore_relayer_api::{
+    consts::*, error::RelayError, event::CompoundEvent, instruction::CompoundArgs, loaders::*,
+};
+use ore_utils::AccountDeserialize;
+use solana_program::{
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    sysvar::{self, Sysvar},
+};
+
+use crate::{
+    collect::{assert_collector, collect_escrow},
+    utils::{assert_active, assert_fee_covered, claim_commission},
+};
+
+/// Collects commission from an escrow which has opted in to compounding, and restakes the net
+/// reward. The reward is claimed into the escrow token account and staked back into the proof,
+/// so the collected balance is unchanged.
+pub fn process_compound<'a, 'info>(
+    accounts: &'a [AccountInfo<'info>],
+    data: &[u8],
+) -> ProgramResult {
+    // Parse args
+    let args = CompoundArgs::try_from_bytes(data)?;
+    let fee = u64::from_le_bytes(args.fee);
+
+    // Load accounts
+    let [signer, config_info, relayer_info, escrow_info, proof_info, vault_info, escrow_tokens_info, treasury_info, treasury_tokens_info, ore_program, token_program, instructions_sysvar] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    load_signer(signer)?;
+    load_relay_config(config_info, false)?;
+    load_any_relayer(relayer_info, true)?;
+    load_any_escrow(escrow_info, true)?;
+    load_token_account(
+        escrow_tokens_info,
+        Some(escrow_info.key),
+        &MINT_ADDRESS,
+        true,
+    )?;
+    load_proof(proof_info, escrow_info.key, true)?;
+    load_token_account(vault_info, Some(relayer_info.key), &MINT_ADDRESS, true)?;
+    load_treasury(treasury_info, false)?;
+    load_treasury_tokens(treasury_tokens_info, true)?;
+    load_program(ore_program, ore_api::id())?;
+    load_program(token_program, spl_token::id())?;
+    load_sysvar(instructions_sysvar, sysvar::instructions::id())?;
+
+    // Error if the program is paused
+    assert_active(config_info)?;
+
+    // Error if fees are claimed through CPI, or exceed the cost of the transaction
+    assert_fee_covered(instructions_sysvar)?;
+
+    // Verify signer is the relayer miner
+    let mut relayer_data = relayer_info.data.borrow_mut();
+    let relayer = Relayer::try_from_bytes_mut(&mut relayer_data)?;
+    assert_collector(signer, relayer)?;
+
+    // Error if the escrow has not opted in to compounding
+    let escrow_data = escrow_info.data.borrow();
+    let escrow = Escrow::try_from_bytes(&escrow_data)?;
+    if escrow.compound.eq(&0) {
+        return Err(RelayError::CompoundDisabled.into());
+    }
+    let last_balance = escrow.last_balance;
+    drop(escrow_data);
+
+    // Collect commission on the latest hash into the relayer vault
+    let slot = Clock::get()?.slot;
+    let Some(commission) = collect_escrow(
+        signer,
+        relayer_info,
+        relayer,
+        escrow_info,
+        proof_info,
+        fee,
+        slot,
+    )?
+    else {
+        return Err(RelayError::DuplicateCollect.into());
+    };
+    drop(relayer_data);
+    claim_commission(
+        escrow_info,
+        proof_info,
+        vault_info,
+        treasury_info,
+        treasury_tokens_info,
+        token_program,
+        commission,
+    )?;
+
+    // The collected balance grows by the reward net of commission
+    let escrow_data = escrow_info.data.borrow();
+    let escrow = Escrow::try_from_bytes(&escrow_data)?;
+    let amount = escrow
+        .last_balance
+        .checked_sub(last_balance)
+        .ok_or(RelayError::BalanceUnderflow)?;
+    let seed_authority = escrow.seed_authority;
+    let escrow_bump = escrow.bump as u8;
+    let last_balance = escrow.last_balance;
+    drop(escrow_data);
+
+    // Claim the net reward to the escrow token account and stake it back into the proof
+    if amount.gt(&0) {
+        let signer_seeds: &[&[u8]] = &[ESCROW, seed_authority.as_ref(), &[escrow_bump]];
+        solana_program::program::invoke_signed(
+            &ore_api::instruction::claim(*escrow_info.key, *escrow_tokens_info.key, amount),
+            &[
+                escrow_info.clone(),
+                escrow_tokens_info.clone(),
+                proof_info.clone(),
+                treasury_info.clone(),
+                treasury_tokens_info.clone(),
+                token_program.clone(),
+            ],
+            &[signer_seeds],
+        )?;
+        solana_program::program::invoke_signed(
+            &ore_api::instruction::stake(*escrow_info.key, *escrow_tokens_info.key, amount),
+            &[
+                escrow_info.clone(),
+                proof_info.clone(),
+                escrow_tokens_info.clone(),
+                treasury_tokens_info.clone(),
+                token_program.clone(),
+            ],
+            &[signer_seeds],
+        )?;
+    }
+
+    // Log event
+    CompoundEvent {
+        escrow: *escrow_info.key,
+        relayer: *relayer_info.key,
+        amount,
+        last_balance,
+    }
+    .log();
+
+    Ok(())
+}
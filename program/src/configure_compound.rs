@@ -0,0 +1,38 @@
+use ore_relayer_api::{
+    event::ConfigureCompoundEvent, instruction::ConfigureCompoundArgs, loaders::*,
+};
+use ore_utils::AccountDeserialize;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+};
+
+/// Opts an escrow in or out of having its net rewards restaked by the relayer.
+pub fn process_configure_compound<'a, 'info>(
+    accounts: &'a [AccountInfo<'info>],
+    data: &[u8],
+) -> ProgramResult {
+    // Parse args
+    let args = ConfigureCompoundArgs::try_from_bytes(data)?;
+    let compound = args.compound.min(1) as u64;
+
+    // Load accounts
+    let [signer, escrow_info] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    load_signer(signer)?;
+    load_escrow(escrow_info, signer.key, true)?;
+
+    // Update compound setting
+    let mut escrow_data = escrow_info.data.borrow_mut();
+    let escrow = Escrow::try_from_bytes_mut(&mut escrow_data)?;
+    escrow.compound = compound;
+
+    // Log event
+    ConfigureCompoundEvent {
+        escrow: *escrow_info.key,
+        compound,
+    }
+    .log();
+
+    Ok(())
+}
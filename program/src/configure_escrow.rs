@@ -0,0 +1,39 @@
+use ore_relayer_api::{event::ConfigureEscrowEvent, instruction::ConfigureEscrowArgs, loaders::*};
+use ore_utils::AccountDeserialize;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+};
+
+/// Configures the fee limits of an escrow account.
+pub fn process_configure_escrow<'a, 'info>(
+    accounts: &'a [AccountInfo<'info>],
+    data: &[u8],
+) -> ProgramResult {
+    // Parse args
+    let args = ConfigureEscrowArgs::try_from_bytes(data)?;
+    let max_fee_per_collect = u64::from_le_bytes(args.max_fee_per_collect);
+    let epoch_fee_budget = u64::from_le_bytes(args.epoch_fee_budget);
+
+    // Load accounts
+    let [signer, escrow_info] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    load_signer(signer)?;
+    load_escrow(escrow_info, signer.key, true)?;
+
+    // Update fee limits
+    let mut escrow_data = escrow_info.data.borrow_mut();
+    let escrow = Escrow::try_from_bytes_mut(&mut escrow_data)?;
+    escrow.max_fee_per_collect = max_fee_per_collect;
+    escrow.epoch_fee_budget = epoch_fee_budget;
+
+    // Log event
+    ConfigureEscrowEvent {
+        escrow: *escrow_info.key,
+        max_fee_per_collect,
+        epoch_fee_budget,
+    }
+    .log();
+
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use ore_api::consts::MINT_ADDRESS;
+use ore_relayer_api::{event::ConfigurePayoutEvent, instruction::ConfigurePayoutArgs, loaders::*};
+use ore_utils::AccountDeserialize;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+};
+
+/// Configures the destination and threshold of relayer-triggered payouts.
+pub fn process_configure_payout<'a, 'info>(
+    accounts: &'a [AccountInfo<'info>],
+    data: &[u8],
+) -> ProgramResult {
+    // Parse args
+    let args = ConfigurePayoutArgs::try_from_bytes(data)?;
+    let threshold = u64::from_le_bytes(args.threshold);
+
+    // Load accounts
+    let [signer, escrow_info, destination_info] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    load_signer(signer)?;
+    load_escrow(escrow_info, signer.key, true)?;
+    load_token_account(destination_info, None, &MINT_ADDRESS, false)?;
+
+    // Update payout settings
+    let mut escrow_data = escrow_info.data.borrow_mut();
+    let escrow = Escrow::try_from_bytes_mut(&mut escrow_data)?;
+    escrow.payout_destination = *destination_info.key;
+    escrow.payout_threshold = threshold;
+
+    // Log event
+    ConfigurePayoutEvent {
+        escrow: *escrow_info.key,
+        destination: *destination_info.key,
+        threshold,
+    }
+    .log();
+
+    Ok(())
+}
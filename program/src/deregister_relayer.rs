@@ -0,0 +1,112 @@
+use ore_api::consts::MINT_ADDRESS;
+use ore_relayer_api::{consts::*, error::RelayError, event::DeregisterRelayerEvent, loaders::*};
+use ore_utils::{spl::transfer_signed, AccountDeserialize};
+use solana_program::{
+    account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult,
+    program_error::ProgramError, program_pack::Pack, system_program, sysvar::Sysvar,
+};
+
+use crate::utils::assert_active;
+
+/// Requests deregistration of a relayer, or closes the relayer account and vault once its bond is
+/// unlocked. Commission settled into the vault during the lockup is paid to the beneficiary.
+pub fn process_deregister_relayer<'a, 'info>(
+    accounts: &'a [AccountInfo<'info>],
+    _data: &[u8],
+) -> ProgramResult {
+    // Load accounts
+    let [signer, config_info, relayer_info, beneficiary_info, vault_info, system_program, token_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    load_signer(signer)?;
+    load_relay_config(config_info, false)?;
+    load_relayer(relayer_info, signer.key, true)?;
+    load_token_account(beneficiary_info, None, &MINT_ADDRESS, true)?;
+    load_token_account(vault_info, Some(relayer_info.key), &MINT_ADDRESS, true)?;
+    load_program(system_program, system_program::id())?;
+    load_program(token_program, spl_token::id())?;
+
+    // Error if the program is paused
+    assert_active(config_info)?;
+
+    // Error if the admin has stopped the relayer, so a leaked key cannot withdraw the bond. The
+    // relayer's own stop does not block deregistration.
+    let mut relayer_data = relayer_info.data.borrow_mut();
+    let relayer = Relayer::try_from_bytes_mut(&mut relayer_data)?;
+    if relayer.admin_paused.gt(&0) {
+        return Err(RelayError::RelayerPaused.into());
+    }
+
+    // Start the bond lockup on the first request
+    let clock = Clock::get()?;
+    if relayer.unbonding_slot.eq(&0) {
+        if relayer.accrued_commission.gt(&0) {
+            return Err(RelayError::UnwithdrawnCommission.into());
+        }
+        relayer.unbonding_slot = clock.slot.saturating_add(BOND_LOCKUP_SLOTS);
+        return Ok(());
+    }
+
+    // Error if the bond is still locked
+    if clock.slot.lt(&relayer.unbonding_slot) {
+        return Err(RelayError::BondLocked.into());
+    }
+
+    // Verify the beneficiary is the one registered by the relayer
+    if beneficiary_info.key.ne(&relayer.beneficiary) {
+        return Err(RelayError::InvalidBeneficiary.into());
+    }
+    let relayer_bump = relayer.bump as u8;
+    drop(relayer_data);
+
+    // Pay out the vault balance and close the vault, returning rent to the signer
+    let signer_seeds: &[&[u8]] = &[RELAYER, signer.key.as_ref(), &[relayer_bump]];
+    let vault_data = vault_info.data.borrow();
+    let vault_balance = spl_token::state::Account::unpack(&vault_data)?.amount;
+    drop(vault_data);
+    if vault_balance.gt(&0) {
+        transfer_signed(
+            relayer_info,
+            vault_info,
+            beneficiary_info,
+            token_program,
+            vault_balance,
+            &[signer_seeds],
+        )?;
+    }
+    solana_program::program::invoke_signed(
+        &spl_token::instruction::close_account(
+            &spl_token::id(),
+            vault_info.key,
+            signer.key,
+            relayer_info.key,
+            &[relayer_info.key],
+        )?,
+        &[
+            token_program.clone(),
+            vault_info.clone(),
+            signer.clone(),
+            relayer_info.clone(),
+        ],
+        &[signer_seeds],
+    )?;
+
+    // Realloc data to zero
+    relayer_info.realloc(0, true)?;
+
+    // Send lamports to signer
+    let amount = relayer_info.lamports();
+    **signer.lamports.borrow_mut() += amount;
+    **relayer_info.lamports.borrow_mut() = 0;
+
+    // Log event
+    DeregisterRelayerEvent {
+        relayer: *relayer_info.key,
+        amount,
+    }
+    .log();
+
+    Ok(())
+}
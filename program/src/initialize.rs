@@ -0,0 +1,49 @@
+use std::mem::size_of;
+
+use ore_relayer_api::{consts::*, error::RelayError, event::InitializeEvent, loaders::*};
+use ore_utils::{create_pda, AccountDeserialize, Discriminator};
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+    system_program,
+};
+
+/// Initializes the program config account.
+pub fn process_initialize<'a, 'info>(
+    accounts: &'a [AccountInfo<'info>],
+    _data: &[u8],
+) -> ProgramResult {
+    // Load accounts
+    let [signer, config_info, system_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    load_signer(signer)?;
+    load_uninitialized_pda(config_info, &[CONFIG], CONFIG_BUMP, &ore_relayer_api::id())?;
+    load_program(system_program, system_program::id())?;
+
+    // Verify signer is the initializer
+    if signer.key.ne(&INITIALIZER_ADDRESS) {
+        return Err(RelayError::UnauthorizedAdmin.into());
+    }
+
+    // Create config account
+    create_pda(
+        config_info,
+        &ore_relayer_api::id(),
+        8 + size_of::<Config>(),
+        &[CONFIG, &[CONFIG_BUMP]],
+        system_program,
+        signer,
+    )?;
+
+    // Initialize config account
+    let mut config_data = config_info.data.borrow_mut();
+    config_data[0] = Config::discriminator();
+    let config = Config::try_from_bytes_mut(&mut config_data)?;
+    config.admin = *signer.key;
+    config.paused = 0;
+
+    // Log event
+    InitializeEvent { admin: *signer.key }.log();
+
+    Ok(())
+}
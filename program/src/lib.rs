@@ -1,16 +1,61 @@
+#![allow(clippy::needless_lifetimes)]
+
+mod accept_authority;
 mod claim;
 mod close_escrow;
 mod collect;
+mod collect_many;
+mod compound;
+mod configure_compound;
+mod configure_escrow;
+mod configure_payout;
+mod deregister_relayer;
+mod initialize;
+mod migrate_escrow;
+mod mine;
 mod open_escrow;
+mod payout;
+mod propose_authority;
+mod register_relayer;
+mod revoke_relayer;
+mod set_admin;
+mod set_paused;
+mod set_relayer_paused;
+mod slash_relayer;
 mod stake;
+mod switch_relayer;
 mod update_miner;
+mod update_relayer;
+mod utils;
+mod withdraw_commission;
 
+use accept_authority::*;
 use claim::*;
 use close_escrow::*;
 use collect::*;
+use collect_many::*;
+use compound::*;
+use configure_compound::*;
+use configure_escrow::*;
+use configure_payout::*;
+use deregister_relayer::*;
+use initialize::*;
+use migrate_escrow::*;
+use mine::*;
 use open_escrow::*;
+use payout::*;
+use propose_authority::*;
+use register_relayer::*;
+use revoke_relayer::*;
+use set_admin::*;
+use set_paused::*;
+use set_relayer_paused::*;
+use slash_relayer::*;
 use stake::*;
+use switch_relayer::*;
 use update_miner::*;
+use update_relayer::*;
+use withdraw_commission::*;
 
 use ore_relayer_api::instruction::*;
 use solana_program::{
@@ -40,10 +85,33 @@ pub fn process_instruction(
         RelayInstruction::Stake => process_stake(accounts, data)?,
         RelayInstruction::OpenEscrow => process_open_escrow(accounts, data)?,
         RelayInstruction::CloseEscrow => process_close_escrow(accounts, data)?,
+        RelayInstruction::ConfigureEscrow => process_configure_escrow(accounts, data)?,
+        RelayInstruction::MigrateEscrow => process_migrate_escrow(accounts, data)?,
+        RelayInstruction::SwitchRelayer => process_switch_relayer(accounts, data)?,
+        RelayInstruction::ConfigureCompound => process_configure_compound(accounts, data)?,
+        RelayInstruction::ConfigurePayout => process_configure_payout(accounts, data)?,
+        RelayInstruction::SlashRelayer => process_slash_relayer(accounts, data)?,
+        RelayInstruction::ProposeAuthority => process_propose_authority(accounts, data)?,
+        RelayInstruction::AcceptAuthority => process_accept_authority(accounts, data)?,
+        RelayInstruction::RevokeRelayer => process_revoke_relayer(accounts, data)?,
 
         // Relayer ixs
         RelayInstruction::Collect => process_collect(accounts, data)?,
         RelayInstruction::UpdateMiner => process_update_miner(accounts, data)?,
+        RelayInstruction::RegisterRelayer => process_register_relayer(accounts, data)?,
+        RelayInstruction::UpdateRelayer => process_update_relayer(accounts, data)?,
+        RelayInstruction::DeregisterRelayer => process_deregister_relayer(accounts, data)?,
+        RelayInstruction::Compound => process_compound(accounts, data)?,
+        RelayInstruction::Payout => process_payout(accounts, data)?,
+        RelayInstruction::WithdrawCommission => process_withdraw_commission(accounts, data)?,
+        RelayInstruction::Mine => process_mine(accounts, data)?,
+        RelayInstruction::CollectMany => process_collect_many(accounts, data)?,
+
+        // Admin ixs
+        RelayInstruction::Initialize => process_initialize(accounts, data)?,
+        RelayInstruction::SetAdmin => process_set_admin(accounts, data)?,
+        RelayInstruction::SetPaused => process_set_paused(accounts, data)?,
+        RelayInstruction::SetRelayerPaused => process_set_relayer_paused(accounts, data)?,
     }
 
     Ok(())
@@ -0,0 +1,119 @@
+use std::mem::size_of;
+
+use ore_api::state::Proof;
+use ore_relayer_api::{
+    consts::*, error::RelayError, event::MigrateEscrowEvent, instruction::MigrateEscrowArgs,
+    loaders::*,
+};
+use ore_utils::AccountDeserialize;
+use solana_program::{
+    account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult,
+    program_error::ProgramError, pubkey::Pubkey, rent::Rent, system_program, sysvar::Sysvar,
+};
+
+use crate::utils::assert_active;
+
+/// Migrates a legacy escrow account to the current layout version. Legacy escrows are bound to
+/// the given relayer, which must be the one mining their proof, at the commission rate the user
+/// expects.
+pub fn process_migrate_escrow<'a, 'info>(
+    accounts: &'a [AccountInfo<'info>],
+    data: &[u8],
+) -> ProgramResult {
+    // Parse args
+    let args = MigrateEscrowArgs::try_from_bytes(data)?;
+    let expected_commission = u64::from_le_bytes(args.expected_commission);
+
+    // Load accounts
+    let [signer, config_info, escrow_info, proof_info, relayer_info, system_program] = accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    load_signer(signer)?;
+    load_relay_config(config_info, false)?;
+    load_escrow_any_version(escrow_info, signer.key, true)?;
+    load_proof(proof_info, escrow_info.key, false)?;
+    load_any_relayer(relayer_info, false)?;
+    load_program(system_program, system_program::id())?;
+
+    // Error if the program is paused
+    assert_active(config_info)?;
+
+    // Error if the escrow is already on the current version
+    let version = Escrow::version_of(&escrow_info.data.borrow())?;
+    if version.ge(&ESCROW_VERSION) {
+        return Err(RelayError::InvalidEscrowVersion.into());
+    }
+
+    // Fund rent exemption for the current layout
+    let size = 8 + size_of::<Escrow>();
+    let rent_exempt_balance = Rent::get()?
+        .minimum_balance(size)
+        .saturating_sub(escrow_info.lamports());
+    if rent_exempt_balance.gt(&0) {
+        solana_program::program::invoke(
+            &solana_program::system_instruction::transfer(
+                signer.key,
+                escrow_info.key,
+                rent_exempt_balance,
+            ),
+            &[signer.clone(), escrow_info.clone(), system_program.clone()],
+        )?;
+    }
+
+    // Realloc data to the current layout. Appended fields are zero initialized.
+    escrow_info.realloc(size, true)?;
+
+    // Initialize fields introduced since the legacy layout
+    let proof_data = proof_info.data.borrow();
+    let proof = Proof::try_from_bytes(&proof_data)?;
+    let relayer_data = relayer_info.data.borrow();
+    let relayer = Relayer::try_from_bytes(&relayer_data)?;
+    let mut escrow_data = escrow_info.data.borrow_mut();
+    let escrow = Escrow::try_from_bytes_mut(&mut escrow_data)?;
+
+    // Legacy escrows are bound to the relayer which mines their proof.
+    if proof.miner.ne(&relayer.miner) {
+        return Err(RelayError::InvalidMiner.into());
+    }
+
+    // Validate the commission rate is the one agreed to by the user
+    if relayer.commission.ne(&expected_commission) {
+        return Err(RelayError::InvalidCommission.into());
+    }
+
+    // Legacy escrows recorded the proof balance before commission was claimed. They cannot have
+    // been transferred, so their authority seeds the PDA.
+    escrow.last_balance = escrow.last_balance.min(proof.balance);
+    escrow.relayer = *relayer_info.key;
+    escrow.commission = relayer.commission;
+    escrow.max_fee_per_collect = DEFAULT_MAX_FEE_PER_COLLECT;
+    escrow.epoch_fee_budget = DEFAULT_EPOCH_FEE_BUDGET;
+    escrow.fee_epoch = 0;
+    escrow.fee_epoch_spent = 0;
+    escrow.compound = 0;
+    escrow.payout_destination = Pubkey::new_from_array([0; 32]);
+    escrow.payout_threshold = 0;
+    escrow.seed_authority = escrow.authority;
+    escrow.pending_authority = Pubkey::new_from_array([0; 32]);
+    escrow.rent_debt = 0;
+    escrow.stats = EscrowStats::default();
+    escrow.pending_miner = Pubkey::new_from_array([0; 32]);
+    escrow.pending_miner_slot = 0;
+    escrow.slashed = 0;
+    escrow.rent_payer = Pubkey::new_from_array([0; 32]);
+
+    // The liveness period of the relayer starts from the migration.
+    escrow.relayer_slot = Clock::get()?.slot;
+    escrow.version = ESCROW_VERSION;
+
+    // Log event
+    MigrateEscrowEvent {
+        escrow: *escrow_info.key,
+        relayer: escrow.relayer,
+        version,
+    }
+    .log();
+
+    Ok(())
+}
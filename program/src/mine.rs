@@ -0,0 +1,106 @@
+use drillx::Solution;
+use ore_api::consts::MINT_ADDRESS;
+use ore_relayer_api::{error::RelayError, instruction::MineArgs, loaders::*};
+use ore_utils::AccountDeserialize;
+use solana_program::{
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    sysvar::{self, Sysvar},
+};
+
+use crate::{
+    collect::{assert_collector, collect_escrow},
+    utils::{assert_active, assert_fee_covered, claim_commission},
+};
+
+/// Mines a proof on behalf of an escrow, and collects commission on the reward in the same
+/// instruction. The reward is measured from the proof balance, so any reward left uncollected
+/// by earlier mines is settled as well.
+pub fn process_mine<'a, 'info>(accounts: &'a [AccountInfo<'info>], data: &[u8]) -> ProgramResult {
+    // Parse args
+    let args = MineArgs::try_from_bytes(data)?;
+    let fee = u64::from_le_bytes(args.fee);
+
+    // Load accounts
+    let [signer, config_info, relayer_info, escrow_info, proof_info, vault_info, bus_info, ore_config_info, treasury_info, treasury_tokens_info, instructions_sysvar, slot_hashes_sysvar, ore_program, token_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    load_signer(signer)?;
+    load_relay_config(config_info, false)?;
+    load_any_relayer(relayer_info, true)?;
+    load_any_escrow(escrow_info, true)?;
+    load_proof(proof_info, escrow_info.key, true)?;
+    load_token_account(vault_info, Some(relayer_info.key), &MINT_ADDRESS, true)?;
+    load_any_bus(bus_info, true)?;
+    load_config(ore_config_info, false)?;
+    load_treasury(treasury_info, false)?;
+    load_treasury_tokens(treasury_tokens_info, true)?;
+    load_sysvar(instructions_sysvar, sysvar::instructions::id())?;
+    load_sysvar(slot_hashes_sysvar, sysvar::slot_hashes::id())?;
+    load_program(ore_program, ore_api::id())?;
+    load_program(token_program, spl_token::id())?;
+
+    // Error if the program is paused
+    assert_active(config_info)?;
+
+    // Error if fees are claimed through CPI, or exceed the cost of the transaction
+    assert_fee_covered(instructions_sysvar)?;
+
+    // Verify signer is the relayer miner
+    let relayer_data = relayer_info.data.borrow();
+    let relayer = Relayer::try_from_bytes(&relayer_data)?;
+    assert_collector(signer, relayer)?;
+    drop(relayer_data);
+
+    // Submit the solution to the ORE program
+    solana_program::program::invoke(
+        &ore_api::instruction::mine(
+            *signer.key,
+            *escrow_info.key,
+            *bus_info.key,
+            Solution::new(args.digest, args.nonce),
+        ),
+        &[
+            signer.clone(),
+            bus_info.clone(),
+            ore_config_info.clone(),
+            proof_info.clone(),
+            instructions_sysvar.clone(),
+            slot_hashes_sysvar.clone(),
+        ],
+    )?;
+
+    // Collect commission on the reward. A successful mine always moves the proof to a new hash.
+    let mut relayer_data = relayer_info.data.borrow_mut();
+    let relayer = Relayer::try_from_bytes_mut(&mut relayer_data)?;
+    let Some(commission) = collect_escrow(
+        signer,
+        relayer_info,
+        relayer,
+        escrow_info,
+        proof_info,
+        fee,
+        Clock::get()?.slot,
+    )?
+    else {
+        return Err(RelayError::DuplicateCollect.into());
+    };
+    drop(relayer_data);
+
+    // Claim commission into the relayer vault
+    claim_commission(
+        escrow_info,
+        proof_info,
+        vault_info,
+        treasury_info,
+        treasury_tokens_info,
+        token_program,
+        commission,
+    )?;
+
+    Ok(())
+}
@@ -4,14 +4,26 @@ use ore_api::{
     consts::{MINT_ADDRESS, PROOF},
     state::Proof,
 };
-use ore_relayer_api::{consts::*, error::RelayError, instruction::OpenEscrowArgs, loaders::*};
+use ore_relayer_api::{
+    consts::*, error::RelayError, event::OpenEscrowEvent, instruction::OpenEscrowArgs, loaders::*,
+};
 use ore_utils::{create_pda, spl::create_ata, AccountDeserialize, Discriminator};
 use solana_program::{
-    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
-    system_program, sysvar,
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program,
+    sysvar::{self, Sysvar},
 };
 
-/// Opens a new escrow account.
+use crate::utils::assert_active;
+
+/// Opens a new escrow account. The payer funds every account opened, so users may be onboarded
+/// without SOL. A payer other than the signer may record the rent it funded, up to the requested
+/// rent debt, as a debt repaid to it in lamports when the escrow closes, rather than out of
+/// commission.
 pub fn process_open_escrow<'a, 'info>(
     accounts: &'a [AccountInfo<'info>],
     data: &[u8],
@@ -20,13 +32,15 @@ pub fn process_open_escrow<'a, 'info>(
     let args = OpenEscrowArgs::try_from_bytes(data)?;
 
     // Load accounts
-    let [signer, miner_info, payer, escrow_info, escrow_tokens, mint_info, proof_info, ore_program, system_program, token_program, associated_token_program, slot_hashes_sysvar] =
+    let [signer, config_info, miner_info, relayer_info, payer, escrow_info, escrow_tokens, mint_info, proof_info, ore_program, system_program, token_program, associated_token_program, slot_hashes_sysvar] =
         accounts
     else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
     load_signer(signer)?;
+    load_relay_config(config_info, false)?;
     load_system_account(miner_info, false)?;
+    load_any_relayer(relayer_info, false)?;
     load_signer(payer)?;
     load_uninitialized_pda(
         proof_info,
@@ -48,19 +62,40 @@ pub fn process_open_escrow<'a, 'info>(
     load_program(system_program, system_program::id())?;
     load_sysvar(slot_hashes_sysvar, sysvar::slot_hashes::id())?;
 
-    // validate miner against relayer
-    if miner_info.key.ne(&MINER_PUBKEY) {
-        return Err(RelayError::Dummy.into());
+    // Error if the program is paused
+    assert_active(config_info)?;
+
+    // Validate the relayer is the one selected by the user
+    if relayer_info.key.ne(&args.relayer) {
+        return Err(RelayError::UnauthorizedRelayer.into());
+    }
+
+    // Validate miner against relayer
+    let relayer_data = relayer_info.data.borrow();
+    let relayer = Relayer::try_from_bytes(&relayer_data)?;
+    if miner_info.key.ne(&relayer.miner) {
+        return Err(RelayError::InvalidMiner.into());
+    }
+    if relayer.is_paused() {
+        return Err(RelayError::RelayerPaused.into());
+    }
+
+    // Validate the commission rate is the one agreed to by the user
+    let commission = u64::from_le_bytes(args.commission);
+    if commission.ne(&relayer.commission) {
+        return Err(RelayError::InvalidCommission.into());
     }
+    drop(relayer_data);
 
     // Create escrow account
+    let payer_lamports = payer.lamports();
     create_pda(
         escrow_info,
         &ore_relayer_api::id(),
         8 + size_of::<Escrow>(),
         &[ESCROW, signer.key.as_ref(), &[args.escrow_bump]],
         system_program,
-        signer,
+        payer,
     )?;
 
     //// Open a proof account for mining
@@ -83,17 +118,33 @@ pub fn process_open_escrow<'a, 'info>(
 
     // Initialize escrow account
     let mut escrow_data = escrow_info.data.borrow_mut();
-    escrow_data[0] = Escrow::discriminator() as u8;
+    escrow_data[0] = Escrow::discriminator();
     let escrow = Escrow::try_from_bytes_mut(&mut escrow_data)?;
     escrow.authority = *signer.key;
     escrow.bump = args.escrow_bump as u64;
     escrow.last_hash = proof.last_hash;
     escrow.last_balance = 0;
+    escrow.version = ESCROW_VERSION;
+    escrow.relayer = args.relayer;
+    escrow.commission = commission;
+    escrow.max_fee_per_collect = DEFAULT_MAX_FEE_PER_COLLECT;
+    escrow.epoch_fee_budget = DEFAULT_EPOCH_FEE_BUDGET;
+    escrow.fee_epoch = 0;
+    escrow.fee_epoch_spent = 0;
+    escrow.compound = 0;
+    escrow.seed_authority = *signer.key;
+    escrow.pending_authority = Pubkey::new_from_array([0; 32]);
+    escrow.rent_debt = 0;
+    escrow.rent_payer = Pubkey::new_from_array([0; 32]);
+    escrow.pending_miner = Pubkey::new_from_array([0; 32]);
+    escrow.pending_miner_slot = 0;
+    escrow.slashed = 0;
+    escrow.relayer_slot = Clock::get()?.slot;
 
     // Initialize escrow tokens account
     drop(escrow_data);
     create_ata(
-        signer,
+        payer,
         escrow_info,
         escrow_tokens,
         mint_info,
@@ -102,5 +153,24 @@ pub fn process_open_escrow<'a, 'info>(
         associated_token_program,
     )?;
 
+    // Record the rent funded by a sponsoring payer, up to the requested rent debt
+    let rent_paid = payer_lamports.saturating_sub(payer.lamports());
+    let rent_debt = u64::from_le_bytes(args.rent_debt).min(rent_paid);
+    if payer.key.ne(signer.key) && rent_debt.gt(&0) {
+        let mut escrow_data = escrow_info.data.borrow_mut();
+        let escrow = Escrow::try_from_bytes_mut(&mut escrow_data)?;
+        escrow.rent_debt = rent_debt;
+        escrow.rent_payer = *payer.key;
+    }
+
+    // Log event
+    OpenEscrowEvent {
+        escrow: *escrow_info.key,
+        authority: *signer.key,
+        relayer: args.relayer,
+        commission,
+    }
+    .log();
+
     Ok(())
 }
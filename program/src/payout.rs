@@ -0,0 +1,111 @@
+use ore_api::{consts::MINT_ADDRESS, state::Proof};
+use ore_relayer_api::{
+    consts::*, error::RelayError, event::PayoutEvent, instruction::PayoutArgs, loaders::*,
+};
+use ore_utils::AccountDeserialize;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError, sysvar,
+};
+
+use crate::{
+    collect::assert_collector,
+    utils::{assert_active, assert_fee_covered, reimburse_fee},
+};
+
+/// Pays out the collected balance of an escrow to its registered destination.
+pub fn process_payout<'a, 'info>(accounts: &'a [AccountInfo<'info>], data: &[u8]) -> ProgramResult {
+    // Parse args
+    let args = PayoutArgs::try_from_bytes(data)?;
+    let fee = u64::from_le_bytes(args.fee);
+
+    // Load accounts
+    let [signer, config_info, relayer_info, escrow_info, proof_info, destination_info, treasury_info, treasury_tokens_info, ore_program, token_program, instructions_sysvar] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    load_signer(signer)?;
+    load_relay_config(config_info, false)?;
+    load_any_relayer(relayer_info, false)?;
+    load_any_escrow(escrow_info, true)?;
+    load_proof(proof_info, escrow_info.key, true)?;
+    load_token_account(destination_info, None, &MINT_ADDRESS, true)?;
+    load_treasury(treasury_info, false)?;
+    load_treasury_tokens(treasury_tokens_info, true)?;
+    load_program(ore_program, ore_api::id())?;
+    load_program(token_program, spl_token::id())?;
+    load_sysvar(instructions_sysvar, sysvar::instructions::id())?;
+
+    // Error if the program is paused
+    assert_active(config_info)?;
+
+    // Error if fees are claimed through CPI, or exceed the cost of the transaction
+    assert_fee_covered(instructions_sysvar)?;
+
+    // Verify signer is the relayer miner
+    let relayer_data = relayer_info.data.borrow();
+    let relayer = Relayer::try_from_bytes(&relayer_data)?;
+    assert_collector(signer, relayer)?;
+    drop(relayer_data);
+
+    // Verify the relayer is the one selected by the escrow
+    let mut escrow_data = escrow_info.data.borrow_mut();
+    let escrow = Escrow::try_from_bytes_mut(&mut escrow_data)?;
+    if escrow.relayer.ne(relayer_info.key) {
+        return Err(RelayError::UnauthorizedRelayer.into());
+    }
+
+    // Verify the destination is the one registered by the user
+    if destination_info.key.ne(&escrow.payout_destination) {
+        return Err(RelayError::InvalidBeneficiary.into());
+    }
+
+    // Error if commission is owed on rewards which have not been collected
+    let proof_data = proof_info.data.borrow();
+    let proof = Proof::try_from_bytes(&proof_data)?;
+    if escrow.last_hash.ne(&proof.last_hash) {
+        return Err(RelayError::UncollectedCommission.into());
+    }
+    drop(proof_data);
+
+    // Error if the collected balance has not reached the threshold
+    if escrow.payout_threshold.eq(&0) || escrow.last_balance.lt(&escrow.payout_threshold) {
+        return Err(RelayError::PayoutThresholdNotMet.into());
+    }
+
+    // Pay out the collected balance
+    let amount = escrow.last_balance;
+    escrow.last_balance = 0;
+    escrow.stats.total_claimed = escrow.stats.total_claimed.saturating_add(amount);
+    let seed_authority = escrow.seed_authority;
+    let escrow_bump = escrow.bump as u8;
+    drop(escrow_data);
+    solana_program::program::invoke_signed(
+        &ore_api::instruction::claim(*escrow_info.key, *destination_info.key, amount),
+        &[
+            escrow_info.clone(),
+            destination_info.clone(),
+            proof_info.clone(),
+            treasury_info.clone(),
+            treasury_tokens_info.clone(),
+            token_program.clone(),
+        ],
+        &[&[ESCROW, seed_authority.as_ref(), &[escrow_bump]]],
+    )?;
+
+    // Send transaction fee to miner
+    let mut escrow_data = escrow_info.data.borrow_mut();
+    let escrow = Escrow::try_from_bytes_mut(&mut escrow_data)?;
+    reimburse_fee(escrow, escrow_info, signer, fee)?;
+
+    // Log event
+    PayoutEvent {
+        escrow: *escrow_info.key,
+        destination: *destination_info.key,
+        amount,
+        fee,
+    }
+    .log();
+
+    Ok(())
+}
@@ -0,0 +1,35 @@
+use ore_relayer_api::{event::ProposeAuthorityEvent, loaders::*};
+use ore_utils::AccountDeserialize;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+};
+
+/// Proposes a new authority for an escrow account. The transfer completes once the proposed
+/// authority accepts it.
+pub fn process_propose_authority<'a, 'info>(
+    accounts: &'a [AccountInfo<'info>],
+    _data: &[u8],
+) -> ProgramResult {
+    // Load accounts
+    let [signer, escrow_info, new_authority_info] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    load_signer(signer)?;
+    load_escrow(escrow_info, signer.key, true)?;
+    load_any(new_authority_info, false)?;
+
+    // Record the pending authority
+    let mut escrow_data = escrow_info.data.borrow_mut();
+    let escrow = Escrow::try_from_bytes_mut(&mut escrow_data)?;
+    escrow.pending_authority = *new_authority_info.key;
+
+    // Log event
+    ProposeAuthorityEvent {
+        escrow: *escrow_info.key,
+        authority: *signer.key,
+        pending_authority: *new_authority_info.key,
+    }
+    .log();
+
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use std::mem::size_of;
+
+use ore_api::consts::MINT_ADDRESS;
+use ore_relayer_api::{
+    consts::*, error::RelayError, event::RegisterRelayerEvent, instruction::RegisterRelayerArgs,
+    loaders::*,
+};
+use ore_utils::{create_pda, spl::create_ata, AccountDeserialize, Discriminator};
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+    system_program,
+};
+
+use crate::utils::assert_active;
+
+/// Registers a new relayer account, posts its bond, and opens the relayer vault which holds
+/// commission until it is withdrawn. The miner signs to prove the relayer controls the keypair
+/// its escrows will name as their miner.
+pub fn process_register_relayer<'a, 'info>(
+    accounts: &'a [AccountInfo<'info>],
+    data: &[u8],
+) -> ProgramResult {
+    // Parse args
+    let args = RegisterRelayerArgs::try_from_bytes(data)?;
+    let commission = u64::from_le_bytes(args.commission);
+    let bond = u64::from_le_bytes(args.bond);
+
+    // Load accounts
+    let [signer, config_info, miner_info, beneficiary_info, relayer_info, vault_info, mint_info, system_program, token_program, associated_token_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    load_signer(signer)?;
+    load_relay_config(config_info, false)?;
+    load_signer(miner_info)?;
+    load_token_account(beneficiary_info, None, &MINT_ADDRESS, false)?;
+    load_uninitialized_pda(
+        relayer_info,
+        &[RELAYER, signer.key.as_ref()],
+        args.bump,
+        &ore_relayer_api::id(),
+    )?;
+    load_system_account(vault_info, true)?;
+    load_mint(mint_info, MINT_ADDRESS, false)?;
+    load_program(system_program, system_program::id())?;
+    load_program(token_program, spl_token::id())?;
+    load_program(associated_token_program, spl_associated_token_account::id())?;
+
+    // Error if the program is paused
+    assert_active(config_info)?;
+
+    // Validate commission rate
+    if commission.gt(&DENOMINATOR_BPS) {
+        return Err(RelayError::InvalidCommission.into());
+    }
+
+    // Validate bond
+    if bond.lt(&MIN_RELAYER_BOND) {
+        return Err(RelayError::InsufficientBond.into());
+    }
+
+    // Create relayer account
+    create_pda(
+        relayer_info,
+        &ore_relayer_api::id(),
+        8 + size_of::<Relayer>(),
+        &[RELAYER, signer.key.as_ref(), &[args.bump]],
+        system_program,
+        signer,
+    )?;
+
+    // Post bond
+    solana_program::program::invoke(
+        &solana_program::system_instruction::transfer(signer.key, relayer_info.key, bond),
+        &[signer.clone(), relayer_info.clone(), system_program.clone()],
+    )?;
+
+    // Open relayer vault
+    create_ata(
+        signer,
+        relayer_info,
+        vault_info,
+        mint_info,
+        system_program,
+        token_program,
+        associated_token_program,
+    )?;
+
+    // Initialize relayer account
+    let mut relayer_data = relayer_info.data.borrow_mut();
+    relayer_data[0] = Relayer::discriminator();
+    let relayer = Relayer::try_from_bytes_mut(&mut relayer_data)?;
+    relayer.authority = *signer.key;
+    relayer.bump = args.bump as u64;
+    relayer.miner = *miner_info.key;
+    relayer.commission = commission;
+    relayer.beneficiary = *beneficiary_info.key;
+    relayer.admin_paused = 0;
+    relayer.self_paused = 0;
+    relayer.bond = bond;
+    relayer.unbonding_slot = 0;
+
+    // Log event
+    RegisterRelayerEvent {
+        relayer: *relayer_info.key,
+        authority: *signer.key,
+        miner: *miner_info.key,
+        beneficiary: *beneficiary_info.key,
+        commission,
+    }
+    .log();
+
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use ore_api::state::Proof;
+use ore_relayer_api::{consts::*, error::RelayError, event::RevokeRelayerEvent, loaders::*};
+use ore_utils::AccountDeserialize;
+use solana_program::{
+    account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult,
+    program_error::ProgramError, pubkey::Pubkey, sysvar::Sysvar,
+};
+
+use crate::utils::assert_active;
+
+/// Revokes a relayer which has stopped collecting from the escrow, and reassigns the miner of the
+/// proof. Rewards mined since the last collect are kept by the user, without commission. Commission
+/// already collected stays in the relayer vault, and the escrow is unbound from the relayer. The escrow may then switch to a new relayer, passing the default pubkey as its
+/// current relayer.
+pub fn process_revoke_relayer<'a, 'info>(
+    accounts: &'a [AccountInfo<'info>],
+    _data: &[u8],
+) -> ProgramResult {
+    // Load accounts
+    let [signer, config_info, escrow_info, relayer_info, miner_info, proof_info, ore_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    load_signer(signer)?;
+    load_relay_config(config_info, false)?;
+    load_escrow(escrow_info, signer.key, true)?;
+    load_any(miner_info, false)?;
+    load_proof(proof_info, escrow_info.key, true)?;
+    load_program(ore_program, ore_api::id())?;
+
+    // Error if the program is paused
+    assert_active(config_info)?;
+
+    // Verify the relayer is the one selected by the escrow, and that the escrow is still bound
+    let mut escrow_data = escrow_info.data.borrow_mut();
+    let escrow = Escrow::try_from_bytes_mut(&mut escrow_data)?;
+    if escrow.relayer.ne(relayer_info.key) || escrow.relayer.eq(&Pubkey::new_from_array([0; 32])) {
+        return Err(RelayError::UnauthorizedRelayer.into());
+    }
+
+    // Error if the relayer has collected from the escrow recently
+    let last_active_slot = escrow.last_active_slot();
+    if !escrow.is_stale(Clock::get()?.slot) {
+        return Err(RelayError::RelayerLive.into());
+    }
+
+    // Reset the escrow to the current proof, forgiving commission for the stale period, and
+    // unbind it from the relayer
+    let proof_data = proof_info.data.borrow();
+    let proof = Proof::try_from_bytes(&proof_data)?;
+    escrow.last_balance = proof.balance;
+    escrow.last_hash = proof.last_hash;
+    escrow.relayer = Pubkey::new_from_array([0; 32]);
+    escrow.commission = 0;
+    escrow.pending_miner = Pubkey::new_from_array([0; 32]);
+    escrow.pending_miner_slot = 0;
+    escrow.slashed = 0;
+    let seed_authority = escrow.seed_authority;
+    let escrow_bump = escrow.bump as u8;
+    drop(escrow_data);
+    drop(proof_data);
+
+    // Update the miner keypair on the proof account
+    solana_program::program::invoke_signed(
+        &ore_api::instruction::update(*escrow_info.key, *miner_info.key),
+        &[escrow_info.clone(), miner_info.clone(), proof_info.clone()],
+        &[&[ESCROW, seed_authority.as_ref(), &[escrow_bump]]],
+    )?;
+
+    // Log event
+    RevokeRelayerEvent {
+        escrow: *escrow_info.key,
+        relayer: *relayer_info.key,
+        miner: *miner_info.key,
+        last_active_slot,
+    }
+    .log();
+
+    Ok(())
+}
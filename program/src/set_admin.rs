@@ -0,0 +1,38 @@
+use ore_relayer_api::{error::RelayError, event::SetAdminEvent, loaders::*};
+use ore_utils::AccountDeserialize;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+};
+
+/// Transfers the admin authority of the program config.
+pub fn process_set_admin<'a, 'info>(
+    accounts: &'a [AccountInfo<'info>],
+    _data: &[u8],
+) -> ProgramResult {
+    // Load accounts
+    let [signer, config_info, new_admin_info] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    load_signer(signer)?;
+    load_relay_config(config_info, true)?;
+    load_any(new_admin_info, false)?;
+
+    // Verify signer is the admin
+    let mut config_data = config_info.data.borrow_mut();
+    let config = Config::try_from_bytes_mut(&mut config_data)?;
+    if config.admin.ne(signer.key) {
+        return Err(RelayError::UnauthorizedAdmin.into());
+    }
+
+    // Update admin
+    config.admin = *new_admin_info.key;
+
+    // Log event
+    SetAdminEvent {
+        admin: *signer.key,
+        new_admin: *new_admin_info.key,
+    }
+    .log();
+
+    Ok(())
+}
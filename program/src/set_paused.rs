@@ -0,0 +1,39 @@
+use ore_relayer_api::{
+    error::RelayError, event::SetPausedEvent, instruction::SetPausedArgs, loaders::*,
+};
+use ore_utils::AccountDeserialize;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+};
+
+/// Pauses or unpauses all relayer-side instructions. User exits remain available while paused.
+pub fn process_set_paused<'a, 'info>(
+    accounts: &'a [AccountInfo<'info>],
+    data: &[u8],
+) -> ProgramResult {
+    // Parse args
+    let args = SetPausedArgs::try_from_bytes(data)?;
+
+    // Load accounts
+    let [signer, config_info] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    load_signer(signer)?;
+    load_relay_config(config_info, true)?;
+
+    // Verify signer is the admin
+    let mut config_data = config_info.data.borrow_mut();
+    let config = Config::try_from_bytes_mut(&mut config_data)?;
+    if config.admin.ne(signer.key) {
+        return Err(RelayError::UnauthorizedAdmin.into());
+    }
+
+    // Update pause state
+    let paused = (args.paused > 0) as u64;
+    config.paused = paused;
+
+    // Log event
+    SetPausedEvent { paused }.log();
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use ore_relayer_api::{
+    error::RelayError, event::SetRelayerPausedEvent, instruction::SetPausedArgs, loaders::*,
+};
+use ore_utils::AccountDeserialize;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+};
+
+/// Pauses or unpauses a single relayer. The admin and the relayer authority each hold their own
+/// stop, and the relayer is halted while either is set.
+pub fn process_set_relayer_paused<'a, 'info>(
+    accounts: &'a [AccountInfo<'info>],
+    data: &[u8],
+) -> ProgramResult {
+    // Parse args
+    let args = SetPausedArgs::try_from_bytes(data)?;
+
+    // Load accounts
+    let [signer, config_info, relayer_info] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    load_signer(signer)?;
+    load_relay_config(config_info, false)?;
+    load_any_relayer(relayer_info, true)?;
+
+    // Update the pause state of the signer. An admin stop can only be cleared by the admin.
+    let config_data = config_info.data.borrow();
+    let config = Config::try_from_bytes(&config_data)?;
+    let mut relayer_data = relayer_info.data.borrow_mut();
+    let relayer = Relayer::try_from_bytes_mut(&mut relayer_data)?;
+    let paused = (args.paused > 0) as u64;
+    if config.admin.eq(signer.key) {
+        relayer.admin_paused = paused;
+    } else if relayer.authority.eq(signer.key) {
+        relayer.self_paused = paused;
+    } else {
+        return Err(RelayError::UnauthorizedAdmin.into());
+    }
+
+    // Log event
+    SetRelayerPausedEvent {
+        relayer: *relayer_info.key,
+        admin_paused: relayer.admin_paused,
+        self_paused: relayer.self_paused,
+    }
+    .log();
+
+    Ok(())
+}
@@ -0,0 +1,229 @@
+use ore_relayer_api::{
+    consts::*,
+    error::RelayError,
+    event::SlashRelayerEvent,
+    instruction::{
+        CollectArgs, CollectManyArgs, CompoundArgs, MineArgs, PayoutArgs, RelayInstruction,
+    },
+    loaders::*,
+};
+use ore_utils::AccountDeserialize;
+use solana_program::{
+    account_info::AccountInfo,
+    ed25519_program,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::{Pubkey, PUBKEY_BYTES},
+    sysvar::{self, instructions},
+};
+
+use crate::utils::assert_active;
+
+/// The position of the escrow account in Collect, Compound, Mine and Payout instructions.
+const ESCROW_INDEX: usize = 3;
+
+/// The position of the first escrow account in a CollectMany instruction. Each escrow is
+/// followed by its proof.
+pub(crate) const COLLECT_MANY_ESCROW_INDEX: usize = 9;
+
+/// Slashes a relayer bond, given a message signed by the relayer miner which claims a fee from
+/// the escrow above the allowed fee: the larger of the slashable fee and the escrow's cap. Only
+/// the overcharge is moved into the escrow, up to the maximum escrow slash, and the rest of the
+/// bond stays locked. Further evidence against the same relayer only pays out the amount by which
+/// it exceeds the largest overcharge already slashed into the escrow.
+///
+/// A collect on an unchanged last hash is not accepted as evidence. The hash is read from the
+/// proof during execution and is not part of the signed message, so a message cannot show it
+/// was unchanged. Such a collect also charges nothing: Collect fails with DuplicateCollect and
+/// CollectMany skips the escrow without charging the fee, so there is no overcharge to slash.
+pub fn process_slash_relayer<'a, 'info>(
+    accounts: &'a [AccountInfo<'info>],
+    _data: &[u8],
+) -> ProgramResult {
+    // Load accounts
+    let [signer, config_info, escrow_info, relayer_info, instructions_sysvar] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    load_signer(signer)?;
+    load_relay_config(config_info, false)?;
+    load_escrow(escrow_info, signer.key, true)?;
+    load_any_relayer(relayer_info, true)?;
+    load_sysvar(instructions_sysvar, sysvar::instructions::id())?;
+
+    // Error if the program is paused. A relayer pause does not shield the bond from slashing.
+    assert_active(config_info)?;
+
+    // Verify the relayer is the one selected by the escrow
+    let mut escrow_data = escrow_info.data.borrow_mut();
+    let escrow = Escrow::try_from_bytes_mut(&mut escrow_data)?;
+    if escrow.relayer.ne(relayer_info.key) {
+        return Err(RelayError::UnauthorizedRelayer.into());
+    }
+
+    // Load the evidence verified by the preceding ed25519 instruction
+    let current_index = instructions::load_current_index_checked(instructions_sysvar)?;
+    let verify_index = current_index
+        .checked_sub(1)
+        .ok_or(RelayError::InvalidEvidence)?;
+    let verify_ix =
+        instructions::load_instruction_at_checked(verify_index as usize, instructions_sysvar)?;
+    if verify_ix.program_id.ne(&ed25519_program::id()) {
+        return Err(RelayError::InvalidEvidence.into());
+    }
+    let (pubkey, message) =
+        parse_ed25519_instruction(&verify_ix.data).ok_or(RelayError::InvalidEvidence)?;
+
+    // Verify the evidence is signed by the relayer miner and overcharges this escrow
+    let mut relayer_data = relayer_info.data.borrow_mut();
+    let relayer = Relayer::try_from_bytes_mut(&mut relayer_data)?;
+    if pubkey.ne(&relayer.miner) {
+        return Err(RelayError::InvalidEvidence.into());
+    }
+    let fee = find_claimed_fee(message, &relayer.miner, escrow_info.key)
+        .ok_or(RelayError::InvalidEvidence)?;
+    let allowed_fee = escrow.max_fee_per_collect.max(SLASHABLE_FEE);
+    let overcharge = fee.saturating_sub(allowed_fee).min(MAX_ESCROW_SLASH);
+    if overcharge.le(&escrow.slashed) {
+        return Err(RelayError::InvalidEvidence.into());
+    }
+
+    // Move the overcharge not yet slashed from the bond into the escrow
+    let amount = overcharge.saturating_sub(escrow.slashed).min(relayer.bond);
+    if amount.eq(&0) {
+        return Err(RelayError::InsufficientBond.into());
+    }
+    relayer.bond -= amount;
+    escrow.slashed = escrow.slashed.saturating_add(amount);
+    drop(escrow_data);
+    **relayer_info.lamports.borrow_mut() -= amount;
+    **escrow_info.lamports.borrow_mut() += amount;
+
+    // Log event
+    SlashRelayerEvent {
+        escrow: *escrow_info.key,
+        relayer: *relayer_info.key,
+        fee,
+        amount,
+    }
+    .log();
+
+    Ok(())
+}
+
+/// Returns the public key and message of an ed25519 program instruction holding a single
+/// signature, with all offsets pointing into the instruction itself.
+fn parse_ed25519_instruction(data: &[u8]) -> Option<(Pubkey, &[u8])> {
+    if data.first()?.ne(&1) {
+        return None;
+    }
+    let offset = |i: usize| -> Option<usize> {
+        let bytes = data.get(2 + i * 2..4 + i * 2)?;
+        Some(u16::from_le_bytes([bytes[0], bytes[1]]) as usize)
+    };
+    for i in [1, 3, 6] {
+        if offset(i)?.ne(&(u16::MAX as usize)) {
+            return None;
+        }
+    }
+    let pubkey_offset = offset(2)?;
+    let message_offset = offset(4)?;
+    let message_size = offset(5)?;
+    let pubkey = data.get(pubkey_offset..pubkey_offset.checked_add(PUBKEY_BYTES)?)?;
+    let message = data.get(message_offset..message_offset.checked_add(message_size)?)?;
+    Some((Pubkey::try_from(pubkey).ok()?, message))
+}
+
+/// Returns the highest fee claimed from the escrow by the relay instructions of a transaction
+/// message signed by the miner. Accounts loaded from lookup tables are not resolved.
+fn find_claimed_fee(message: &[u8], miner: &Pubkey, escrow: &Pubkey) -> Option<u64> {
+    let mut cursor = 0;
+
+    // Skip the version prefix of versioned messages
+    if message.first()? & 0x80 != 0 {
+        cursor += 1;
+    }
+
+    // Parse header and static account keys
+    let num_signers = *message.get(cursor)? as usize;
+    cursor += 3;
+    let num_keys = read_compact_u16(message, &mut cursor)?;
+    let keys = read_bytes(message, &mut cursor, num_keys.checked_mul(PUBKEY_BYTES)?)?;
+    let key_at = |i: usize| keys.get(i * PUBKEY_BYTES..(i + 1) * PUBKEY_BYTES);
+    if !(0..num_signers.min(num_keys)).any(|i| key_at(i).eq(&Some(miner.as_ref()))) {
+        return None;
+    }
+
+    // Skip the recent blockhash
+    read_bytes(message, &mut cursor, 32)?;
+
+    // Find fee-claiming instructions against the escrow
+    let mut fee = None;
+    let num_ixs = read_compact_u16(message, &mut cursor)?;
+    for _ in 0..num_ixs {
+        let program_index = *read_bytes(message, &mut cursor, 1)?.first()? as usize;
+        let num_accounts = read_compact_u16(message, &mut cursor)?;
+        let account_indexes = read_bytes(message, &mut cursor, num_accounts)?;
+        let data_len = read_compact_u16(message, &mut cursor)?;
+        let data = read_bytes(message, &mut cursor, data_len)?;
+        if key_at(program_index).ne(&Some(ore_relayer_api::id().as_ref())) {
+            continue;
+        }
+        let Some((tag, args)) = data.split_first() else {
+            continue;
+        };
+        let (ix_fee, escrow_indexes) = match RelayInstruction::try_from(*tag) {
+            Ok(RelayInstruction::Collect) => (
+                CollectArgs::try_from_bytes(args).map(|a| a.fee),
+                ESCROW_INDEX..ESCROW_INDEX + 1,
+            ),
+            Ok(RelayInstruction::Compound) => (
+                CompoundArgs::try_from_bytes(args).map(|a| a.fee),
+                ESCROW_INDEX..ESCROW_INDEX + 1,
+            ),
+            Ok(RelayInstruction::Mine) => (
+                MineArgs::try_from_bytes(args).map(|a| a.fee),
+                ESCROW_INDEX..ESCROW_INDEX + 1,
+            ),
+            Ok(RelayInstruction::Payout) => (
+                PayoutArgs::try_from_bytes(args).map(|a| a.fee),
+                ESCROW_INDEX..ESCROW_INDEX + 1,
+            ),
+            Ok(RelayInstruction::CollectMany) => (
+                CollectManyArgs::try_from_bytes(args).map(|a| a.fee),
+                COLLECT_MANY_ESCROW_INDEX..account_indexes.len(),
+            ),
+            _ => continue,
+        };
+        let Ok(ix_fee) = ix_fee.map(u64::from_le_bytes) else {
+            continue;
+        };
+        let against_escrow = escrow_indexes
+            .step_by(2)
+            .filter_map(|i| account_indexes.get(i))
+            .any(|i| key_at(*i as usize).eq(&Some(escrow.as_ref())));
+        if !against_escrow {
+            continue;
+        }
+        fee = Some(fee.map_or(ix_fee, |f: u64| f.max(ix_fee)));
+    }
+    fee
+}
+
+fn read_bytes<'a>(data: &'a [u8], cursor: &mut usize, len: usize) -> Option<&'a [u8]> {
+    let bytes = data.get(*cursor..cursor.checked_add(len)?)?;
+    *cursor += len;
+    Some(bytes)
+}
+
+fn read_compact_u16(data: &[u8], cursor: &mut usize) -> Option<usize> {
+    let mut value = 0;
+    for i in 0..3 {
+        let byte = *data.get(*cursor)? as usize;
+        *cursor += 1;
+        value |= (byte & 0x7f) << (i * 7);
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
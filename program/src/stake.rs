@@ -1,10 +1,14 @@
 use ore_api::consts::MINT_ADDRESS;
-use ore_relayer_api::{consts::*, instruction::StakeArgs, loaders::*};
+use ore_relayer_api::{
+    consts::*, error::RelayError, event::StakeEvent, instruction::StakeArgs, loaders::*,
+};
 use ore_utils::AccountDeserialize;
 use solana_program::{
     account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
 };
 
+use crate::utils::assert_active;
+
 /// Stakes ORE with the user's proof account.
 pub fn process_stake<'a, 'info>(accounts: &'a [AccountInfo<'info>], data: &[u8]) -> ProgramResult {
     // Parse args
@@ -12,12 +16,13 @@ pub fn process_stake<'a, 'info>(accounts: &'a [AccountInfo<'info>], data: &[u8])
     let amount = u64::from_le_bytes(args.amount);
 
     // Load accounts.
-    let [signer, escrow_info, escrow_tokens_info, proof_info, sender_info, treasury_tokens_info, ore_program, token_program] =
+    let [signer, config_info, escrow_info, escrow_tokens_info, proof_info, sender_info, treasury_tokens_info, ore_program, token_program] =
         accounts
     else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
     load_signer(signer)?;
+    load_relay_config(config_info, false)?;
     load_escrow(escrow_info, signer.key, true)?;
     load_token_account(
         escrow_tokens_info,
@@ -31,6 +36,9 @@ pub fn process_stake<'a, 'info>(accounts: &'a [AccountInfo<'info>], data: &[u8])
     load_program(ore_program, ore_api::id())?;
     load_program(token_program, spl_token::id())?;
 
+    // Error if the program is paused
+    assert_active(config_info)?;
+
     // Transfer tokens from sender to escrow account.
     solana_program::program::invoke(
         &spl_token::instruction::transfer(
@@ -52,10 +60,16 @@ pub fn process_stake<'a, 'info>(accounts: &'a [AccountInfo<'info>], data: &[u8])
     // Increment last balance
     let mut escrow_data = escrow_info.data.borrow_mut();
     let escrow = Escrow::try_from_bytes_mut(&mut escrow_data)?;
-    escrow.last_balance += amount;
+    escrow.last_balance = escrow
+        .last_balance
+        .checked_add(amount)
+        .ok_or(RelayError::BalanceOverflow)?;
+    escrow.stats.total_staked = escrow.stats.total_staked.saturating_add(amount);
 
     // Stake ORE from escrow account
+    let seed_authority = escrow.seed_authority;
     let escrow_bump = escrow.bump as u8;
+    let last_balance = escrow.last_balance;
     drop(escrow_data);
     solana_program::program::invoke_signed(
         &ore_api::instruction::stake(*escrow_info.key, *escrow_tokens_info.key, amount),
@@ -67,8 +81,16 @@ pub fn process_stake<'a, 'info>(accounts: &'a [AccountInfo<'info>], data: &[u8])
             treasury_tokens_info.clone(),
             token_program.clone(),
         ],
-        &[&[ESCROW, signer.key.as_ref(), &[escrow_bump]]],
+        &[&[ESCROW, seed_authority.as_ref(), &[escrow_bump]]],
     )?;
 
+    // Log event
+    StakeEvent {
+        escrow: *escrow_info.key,
+        amount,
+        last_balance,
+    }
+    .log();
+
     Ok(())
 }
@@ -0,0 +1,127 @@
+use ore_api::{consts::MINT_ADDRESS, state::Proof};
+use ore_relayer_api::{
+    consts::*, error::RelayError, event::SwitchRelayerEvent, instruction::SwitchRelayerArgs,
+    loaders::*,
+};
+use ore_utils::AccountDeserialize;
+use solana_program::{
+    account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult,
+    program_error::ProgramError, pubkey::Pubkey, sysvar::Sysvar,
+};
+
+use crate::utils::{assert_active, claim_commission, settle_commission};
+
+/// Switches an escrow to a new relayer, settling any commission owed to the current relayer into
+/// its vault.
+pub fn process_switch_relayer<'a, 'info>(
+    accounts: &'a [AccountInfo<'info>],
+    data: &[u8],
+) -> ProgramResult {
+    // Parse args
+    let args = SwitchRelayerArgs::try_from_bytes(data)?;
+    let commission = u64::from_le_bytes(args.commission);
+
+    // Load accounts
+    let [signer, config_info, escrow_info, proof_info, relayer_info, vault_info, new_relayer_info, miner_info, treasury_info, treasury_tokens_info, ore_program, token_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    load_signer(signer)?;
+    load_relay_config(config_info, false)?;
+    load_escrow(escrow_info, signer.key, true)?;
+    load_proof(proof_info, escrow_info.key, true)?;
+    load_any_relayer(new_relayer_info, false)?;
+    load_any(miner_info, false)?;
+    load_treasury(treasury_info, false)?;
+    load_treasury_tokens(treasury_tokens_info, true)?;
+    load_program(ore_program, ore_api::id())?;
+    load_program(token_program, spl_token::id())?;
+
+    // Error if the program is paused
+    assert_active(config_info)?;
+
+    // Validate miner and commission rate against the new relayer
+    let new_relayer_data = new_relayer_info.data.borrow();
+    let new_relayer = Relayer::try_from_bytes(&new_relayer_data)?;
+    if miner_info.key.ne(&new_relayer.miner) {
+        return Err(RelayError::InvalidMiner.into());
+    }
+    if new_relayer.is_paused() {
+        return Err(RelayError::RelayerPaused.into());
+    }
+    if commission.ne(&new_relayer.commission) {
+        return Err(RelayError::InvalidCommission.into());
+    }
+    drop(new_relayer_data);
+
+    // Verify the current relayer is the one selected by the escrow
+    let mut escrow_data = escrow_info.data.borrow_mut();
+    let escrow = Escrow::try_from_bytes_mut(&mut escrow_data)?;
+    if escrow.relayer.ne(relayer_info.key) {
+        return Err(RelayError::UnauthorizedRelayer.into());
+    }
+
+    // Settle commission owed to the current relayer. If the escrow was unbound by a revoke or the
+    // relayer has deregistered, nothing is owed.
+    let proof_data = proof_info.data.borrow();
+    let proof = Proof::try_from_bytes(&proof_data)?;
+    let unbound = escrow.relayer.eq(&Pubkey::new_from_array([0; 32]));
+    let settled = if unbound || relayer_info.data_is_empty() {
+        escrow.last_balance = proof.balance;
+        escrow.last_hash = proof.last_hash;
+        0
+    } else {
+        load_any_relayer(relayer_info, true)?;
+        load_token_account(vault_info, Some(relayer_info.key), &MINT_ADDRESS, true)?;
+        let mut relayer_data = relayer_info.data.borrow_mut();
+        let relayer = Relayer::try_from_bytes_mut(&mut relayer_data)?;
+        let (_, settled) = settle_commission(escrow, proof)?;
+        relayer.accrued_commission = relayer
+            .accrued_commission
+            .checked_add(settled)
+            .ok_or(RelayError::BalanceOverflow)?;
+        settled
+    };
+
+    // Rebind escrow to the new relayer
+    escrow.relayer = *new_relayer_info.key;
+    escrow.commission = commission;
+    escrow.pending_miner = Pubkey::new_from_array([0; 32]);
+    escrow.pending_miner_slot = 0;
+    escrow.slashed = 0;
+    escrow.relayer_slot = Clock::get()?.slot;
+    let seed_authority = escrow.seed_authority;
+    let escrow_bump = escrow.bump as u8;
+    drop(escrow_data);
+    drop(proof_data);
+
+    // Claim commission to the vault of the current relayer
+    claim_commission(
+        escrow_info,
+        proof_info,
+        vault_info,
+        treasury_info,
+        treasury_tokens_info,
+        token_program,
+        settled,
+    )?;
+
+    // Update the miner keypair on the proof account
+    solana_program::program::invoke_signed(
+        &ore_api::instruction::update(*escrow_info.key, *miner_info.key),
+        &[escrow_info.clone(), miner_info.clone(), proof_info.clone()],
+        &[&[ESCROW, seed_authority.as_ref(), &[escrow_bump]]],
+    )?;
+
+    // Log event
+    SwitchRelayerEvent {
+        escrow: *escrow_info.key,
+        relayer: *relayer_info.key,
+        new_relayer: *new_relayer_info.key,
+        commission: settled,
+    }
+    .log();
+
+    Ok(())
+}
@@ -1,35 +1,73 @@
-use ore_relayer_api::{consts::*, loaders::*};
+use ore_api::state::Proof;
+use ore_relayer_api::{consts::*, event::UpdateMinerEvent, loaders::*};
 use ore_utils::AccountDeserialize;
 use solana_program::{
-    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+    account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult,
+    program_error::ProgramError, pubkey::Pubkey, sysvar::Sysvar,
 };
 
-/// Updates the miner authority for a particular proof account.
+use crate::utils::assert_active;
+
+/// Updates the miner authority for a particular proof account. The change is scheduled, and only
+/// takes effect once the relayer has collected commission on the current hash, or the delay has
+/// elapsed. Calling again with the pending miner applies a scheduled change.
 pub fn process_update_miner<'a, 'info>(
     accounts: &'a [AccountInfo<'info>],
     _data: &[u8],
 ) -> ProgramResult {
     // Load accounts.
-    let [signer, escrow_info, miner_info, proof_info, ore_program] = accounts else {
+    let [signer, config_info, escrow_info, miner_info, proof_info, ore_program] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
     load_signer(signer)?;
+    load_relay_config(config_info, false)?;
     load_escrow(escrow_info, signer.key, true)?;
     load_any(miner_info, false)?;
     load_proof(proof_info, escrow_info.key, true)?;
     load_program(ore_program, ore_api::id())?;
 
-    // Update the miner keypair on the proof account.
-    let escrow_data = escrow_info.data.borrow();
-    let escrow = Escrow::try_from_bytes(&escrow_data)?;
-    let escrow_authority = escrow.authority;
+    // Error if the program is paused
+    assert_active(config_info)?;
+
+    // Schedule the change, unless this miner is already pending
+    let slot = Clock::get()?.slot;
+    let mut escrow_data = escrow_info.data.borrow_mut();
+    let escrow = Escrow::try_from_bytes_mut(&mut escrow_data)?;
+    if escrow.pending_miner.ne(miner_info.key) {
+        escrow.pending_miner = *miner_info.key;
+        escrow.pending_miner_slot = slot.saturating_add(MINER_CHANGE_DELAY_SLOTS);
+    }
+
+    // Apply the change once commission on the current hash is collected, or the delay elapses
+    let proof_data = proof_info.data.borrow();
+    let proof = Proof::try_from_bytes(&proof_data)?;
+    let effective_slot = escrow.pending_miner_slot;
+    let ready = escrow.last_hash.eq(&proof.last_hash) || slot.ge(&effective_slot);
+    if ready {
+        escrow.pending_miner = Pubkey::new_from_array([0; 32]);
+        escrow.pending_miner_slot = 0;
+    }
+    let seed_authority = escrow.seed_authority;
     let escrow_bump = escrow.bump as u8;
     drop(escrow_data);
-    solana_program::program::invoke_signed(
-        &ore_api::instruction::update(*escrow_info.key, *miner_info.key),
-        &[escrow_info.clone(), miner_info.clone(), proof_info.clone()],
-        &[&[ESCROW, escrow_authority.as_ref(), &[escrow_bump]]],
-    )?;
+    drop(proof_data);
+
+    // Update the miner keypair on the proof account.
+    if ready {
+        solana_program::program::invoke_signed(
+            &ore_api::instruction::update(*escrow_info.key, *miner_info.key),
+            &[escrow_info.clone(), miner_info.clone(), proof_info.clone()],
+            &[&[ESCROW, seed_authority.as_ref(), &[escrow_bump]]],
+        )?;
+    }
+
+    // Log event
+    UpdateMinerEvent {
+        escrow: *escrow_info.key,
+        miner: *miner_info.key,
+        effective_slot: if ready { slot } else { effective_slot },
+    }
+    .log();
 
     Ok(())
 }
@@ -0,0 +1,55 @@
+use ore_api::consts::MINT_ADDRESS;
+use ore_relayer_api::{
+    consts::*, error::RelayError, event::UpdateRelayerEvent, instruction::UpdateRelayerArgs,
+    loaders::*,
+};
+use ore_utils::AccountDeserialize;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+};
+
+use crate::utils::assert_active;
+
+/// Updates the commission rate and beneficiary of a relayer account. The miner is fixed at
+/// registration, since the proofs of existing escrows name it as their miner.
+pub fn process_update_relayer<'a, 'info>(
+    accounts: &'a [AccountInfo<'info>],
+    data: &[u8],
+) -> ProgramResult {
+    // Parse args
+    let args = UpdateRelayerArgs::try_from_bytes(data)?;
+    let commission = u64::from_le_bytes(args.commission);
+
+    // Load accounts
+    let [signer, config_info, beneficiary_info, relayer_info] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    load_signer(signer)?;
+    load_relay_config(config_info, false)?;
+    load_token_account(beneficiary_info, None, &MINT_ADDRESS, false)?;
+    load_relayer(relayer_info, signer.key, true)?;
+
+    // Error if the program is paused
+    assert_active(config_info)?;
+
+    // Validate commission rate
+    if commission.gt(&DENOMINATOR_BPS) {
+        return Err(RelayError::InvalidCommission.into());
+    }
+
+    // Update the relayer. Existing escrows keep the commission rate they opened with.
+    let mut relayer_data = relayer_info.data.borrow_mut();
+    let relayer = Relayer::try_from_bytes_mut(&mut relayer_data)?;
+    relayer.commission = commission;
+    relayer.beneficiary = *beneficiary_info.key;
+
+    // Log event
+    UpdateRelayerEvent {
+        relayer: *relayer_info.key,
+        beneficiary: *beneficiary_info.key,
+        commission,
+    }
+    .log();
+
+    Ok(())
+}
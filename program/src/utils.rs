@@ -0,0 +1,513 @@
+use std::mem::size_of;
+
+use ore_api::state::Proof;
+use ore_relayer_api::{
+    consts::{COMPUTE_BUDGET_PROGRAM_ID, ESCROW, LAMPORTS_PER_SIGNATURE},
+    error::RelayError,
+    instruction::{
+        CollectArgs, CollectManyArgs, CompoundArgs, MineArgs, PayoutArgs, RelayInstruction,
+    },
+    state::{Config, Escrow},
+};
+use ore_utils::AccountDeserialize;
+use solana_program::{
+    account_info::AccountInfo,
+    clock::Clock,
+    ed25519_program,
+    entrypoint::ProgramResult,
+    instruction::{get_stack_height, Instruction, TRANSACTION_LEVEL_STACK_HEIGHT},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    secp256k1_program,
+    sysvar::{instructions, Sysvar},
+};
+
+use crate::slash_relayer::COLLECT_MANY_ESCROW_INDEX;
+
+/// Records the reward mined by a proof since the last collect, net of commission. The commission
+/// is left in the proof balance, to be claimed into the relayer vault with claim_commission.
+/// Returns the reward and the commission owed to the relayer on it.
+pub fn settle_commission(escrow: &mut Escrow, proof: &Proof) -> Result<(u64, u64), ProgramError> {
+    let reward = proof
+        .balance
+        .checked_sub(escrow.last_balance)
+        .ok_or(RelayError::BalanceUnderflow)?;
+    let commission = escrow
+        .commission_on(reward)
+        .ok_or(RelayError::BalanceOverflow)?;
+    escrow.stats.total_commission = escrow.stats.total_commission.saturating_add(commission);
+    escrow.last_balance = proof
+        .balance
+        .checked_sub(commission)
+        .ok_or(RelayError::BalanceUnderflow)?;
+    escrow.last_hash = proof.last_hash;
+    Ok((reward, commission))
+}
+
+/// Claims commission settled by an escrow from its proof into the relayer vault. The caller adds
+/// it to the accrued commission of the relayer.
+pub fn claim_commission<'info>(
+    escrow_info: &AccountInfo<'info>,
+    proof_info: &AccountInfo<'info>,
+    vault_info: &AccountInfo<'info>,
+    treasury_info: &AccountInfo<'info>,
+    treasury_tokens_info: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    commission: u64,
+) -> ProgramResult {
+    if commission.eq(&0) {
+        return Ok(());
+    }
+    let escrow_data = escrow_info.data.borrow();
+    let escrow = Escrow::try_from_bytes(&escrow_data)?;
+    let seed_authority = escrow.seed_authority;
+    let escrow_bump = escrow.bump as u8;
+    drop(escrow_data);
+    solana_program::program::invoke_signed(
+        &ore_api::instruction::claim(*escrow_info.key, *vault_info.key, commission),
+        &[
+            escrow_info.clone(),
+            vault_info.clone(),
+            proof_info.clone(),
+            treasury_info.clone(),
+            treasury_tokens_info.clone(),
+            token_program.clone(),
+        ],
+        &[&[ESCROW, seed_authority.as_ref(), &[escrow_bump]]],
+    )
+}
+
+/// Reimburses a relayer for the SOL fee of a transaction sent on behalf of an escrow.
+///
+/// Errors if:
+/// - Fee exceeds the maximum fee per collect.
+/// - Fee exceeds the remaining fee budget of the current epoch.
+/// - Fee exceeds the escrow lamports held above rent exemption.
+pub fn reimburse_fee<'info>(
+    escrow: &mut Escrow,
+    escrow_info: &AccountInfo<'info>,
+    recipient_info: &AccountInfo<'info>,
+    fee: u64,
+) -> ProgramResult {
+    // Error if the fee exceeds the limit set by the user
+    if fee.gt(&escrow.max_fee_per_collect) {
+        return Err(RelayError::FeeCapExceeded.into());
+    }
+
+    // Error if the fee exceeds the remaining budget for this epoch
+    let epoch = Clock::get()?.epoch;
+    if escrow.fee_epoch.ne(&epoch) {
+        escrow.fee_epoch = epoch;
+        escrow.fee_epoch_spent = 0;
+    }
+    escrow.fee_epoch_spent = escrow
+        .fee_epoch_spent
+        .checked_add(fee)
+        .filter(|spent| spent.le(&escrow.epoch_fee_budget))
+        .ok_or(RelayError::EpochBudgetExceeded)?;
+
+    // Error if the fee would leave the escrow below rent exemption
+    // The escrow data is borrowed by the caller, so its length is taken from the layout.
+    let min_balance = Rent::get()?.minimum_balance(8 + size_of::<Escrow>());
+    let surplus = escrow_info.lamports().saturating_sub(min_balance);
+    if fee.gt(&surplus) {
+        return Err(RelayError::InsufficientEscrowLamports.into());
+    }
+
+    // Send fee to recipient
+    **escrow_info.lamports.borrow_mut() -= fee;
+    **recipient_info.lamports.borrow_mut() += fee;
+    escrow.stats.total_reimbursed = escrow.stats.total_reimbursed.saturating_add(fee);
+
+    Ok(())
+}
+
+/// Errors if the program is paused. The pause halts every instruction which moves funds or
+/// settles commission, except the user exits Claim and CloseEscrow. Instructions which only
+/// update escrow settings, and the admin instructions, are not halted.
+pub fn assert_active(config_info: &AccountInfo<'_>) -> ProgramResult {
+    let config_data = config_info.data.borrow();
+    let config = Config::try_from_bytes(&config_data)?;
+    if config.paused.gt(&0) {
+        return Err(RelayError::ProgramPaused.into());
+    }
+    Ok(())
+}
+
+/// The compute unit limit the runtime applies to each instruction, absent a ComputeBudget limit.
+const DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT: u64 = 200_000;
+
+/// The maximum compute unit limit of a transaction.
+const MAX_COMPUTE_UNIT_LIMIT: u64 = 1_400_000;
+
+/// Errors if the fees claimed by the relay instructions of the current transaction exceed what
+/// the transaction costs. Only top-level instructions are listed in the instructions sysvar, so
+/// fees may not be claimed through cross-program invocation.
+pub fn assert_fee_covered(instructions_sysvar: &AccountInfo<'_>) -> ProgramResult {
+    if get_stack_height().ne(&TRANSACTION_LEVEL_STACK_HEIGHT) {
+        return Err(RelayError::FeeClaimedByCpi.into());
+    }
+    let data = instructions_sysvar.try_borrow_data()?;
+    let len = data
+        .get(0..2)
+        .map(|len| u16::from_le_bytes([len[0], len[1]]))
+        .ok_or(ProgramError::InvalidAccountData)?;
+    drop(data);
+    let ixs = (0..len as usize)
+        .map(|index| instructions::load_instruction_at_checked(index, instructions_sysvar))
+        .collect::<Result<Vec<_>, _>>()?;
+    if claimed_fees(&ixs)?.gt(&transaction_cost(&ixs)) {
+        return Err(RelayError::FeeExceedsTransactionCost.into());
+    }
+    Ok(())
+}
+
+/// Returns the fee, in lamports, the runtime charges for a transaction of the given
+/// instructions: the base fee of each signature, plus the priority fee requested by ComputeBudget
+/// instructions.
+fn transaction_cost(ixs: &[Instruction]) -> u64 {
+    let mut signers: Vec<Pubkey> = vec![];
+    let mut signatures: u64 = 0;
+    let mut compute_unit_limit: Option<u64> = None;
+    let mut compute_unit_price: u64 = 0;
+    let mut instructions: u64 = 0;
+    for ix in ixs {
+        for meta in ix.accounts.iter().filter(|meta| meta.is_signer) {
+            if !signers.contains(&meta.pubkey) {
+                signers.push(meta.pubkey);
+            }
+        }
+        if ix.program_id.eq(&COMPUTE_BUDGET_PROGRAM_ID) {
+            match ix.data.split_first() {
+                Some((2, units)) if units.len().eq(&4) => {
+                    compute_unit_limit = Some(u32::from_le_bytes(units.try_into().unwrap()) as u64);
+                }
+                Some((3, price)) if price.len().eq(&8) => {
+                    compute_unit_price = u64::from_le_bytes(price.try_into().unwrap());
+                }
+                _ => {}
+            }
+            continue;
+        }
+        if ix.program_id.eq(&ed25519_program::id()) || ix.program_id.eq(&secp256k1_program::id()) {
+            signatures += ix.data.first().copied().unwrap_or(0) as u64;
+        }
+        instructions += 1;
+    }
+    signatures += signers.len().max(1) as u64;
+
+    // Priority fee is the compute unit price, in micro-lamports, times the compute unit limit
+    let compute_unit_limit = compute_unit_limit
+        .unwrap_or(instructions * DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT)
+        .min(MAX_COMPUTE_UNIT_LIMIT);
+    let priority_fee =
+        (compute_unit_price as u128 * compute_unit_limit as u128).div_ceil(1_000_000);
+    (signatures as u128 * LAMPORTS_PER_SIGNATURE as u128 + priority_fee)
+        .try_into()
+        .unwrap_or(u64::MAX)
+}
+
+/// Returns the total fee, in lamports, claimed for reimbursement by the relay instructions of a
+/// transaction. Batched collects claim the fee once per escrow.
+fn claimed_fees(ixs: &[Instruction]) -> Result<u64, ProgramError> {
+    let mut claimed: u64 = 0;
+    for ix in ixs
+        .iter()
+        .filter(|ix| ix.program_id.eq(&ore_relayer_api::id()))
+    {
+        let Some((tag, args)) = ix.data.split_first() else {
+            continue;
+        };
+        let fee = match RelayInstruction::try_from(*tag) {
+            Ok(RelayInstruction::Collect) => {
+                u64::from_le_bytes(CollectArgs::try_from_bytes(args)?.fee)
+            }
+            Ok(RelayInstruction::CollectMany) => {
+                let escrows = ix.accounts.len().saturating_sub(COLLECT_MANY_ESCROW_INDEX) / 2;
+                u64::from_le_bytes(CollectManyArgs::try_from_bytes(args)?.fee)
+                    .checked_mul(escrows as u64)
+                    .ok_or(RelayError::BalanceOverflow)?
+            }
+            Ok(RelayInstruction::Compound) => {
+                u64::from_le_bytes(CompoundArgs::try_from_bytes(args)?.fee)
+            }
+            Ok(RelayInstruction::Mine) => u64::from_le_bytes(MineArgs::try_from_bytes(args)?.fee),
+            Ok(RelayInstruction::Payout) => {
+                u64::from_le_bytes(PayoutArgs::try_from_bytes(args)?.fee)
+            }
+            _ => 0,
+        };
+        claimed = claimed
+            .checked_add(fee)
+            .ok_or(RelayError::BalanceOverflow)?;
+    }
+    Ok(claimed)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use ore_relayer_api::instruction::{collect, collect_many};
+    use solana_program::{
+        entrypoint::SUCCESS,
+        instruction::AccountMeta,
+        program_stubs::{set_syscall_stubs, SyscallStubs},
+    };
+
+    use super::*;
+
+    thread_local! {
+        static EPOCH: Cell<u64> = const { Cell::new(0) };
+    }
+
+    /// Serves the clock and rent sysvars, with the epoch of the current test thread.
+    struct SysvarStubs;
+
+    impl SyscallStubs for SysvarStubs {
+        fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+            let clock = Clock {
+                epoch: EPOCH.with(|epoch| epoch.get()),
+                ..Clock::default()
+            };
+            unsafe { *(var_addr as *mut Clock) = clock };
+            SUCCESS
+        }
+
+        fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+            unsafe { *(var_addr as *mut Rent) = Rent::default() };
+            SUCCESS
+        }
+    }
+
+    fn proof(balance: u64, last_hash: [u8; 32]) -> Proof {
+        Proof {
+            authority: Pubkey::new_unique(),
+            balance,
+            challenge: [0; 32],
+            last_hash,
+            last_hash_at: 0,
+            last_stake_at: 0,
+            miner: Pubkey::new_unique(),
+            total_hashes: 0,
+            total_rewards: 0,
+        }
+    }
+
+    fn escrow_rent() -> u64 {
+        Rent::default().minimum_balance(8 + size_of::<Escrow>())
+    }
+
+    /// Reimburses a fee from an escrow account holding the given lamports, and returns the
+    /// lamports left in the escrow and received by the recipient.
+    fn reimburse_fee_from(
+        escrow: &mut Escrow,
+        lamports: u64,
+        fee: u64,
+    ) -> Result<(u64, u64), ProgramError> {
+        set_syscall_stubs(Box::new(SysvarStubs));
+        let (escrow_key, recipient_key, owner) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let (mut escrow_lamports, mut recipient_lamports) = (lamports, 0);
+        let mut escrow_data = vec![0; 8 + size_of::<Escrow>()];
+        let mut recipient_data = vec![];
+        let escrow_info = AccountInfo::new(
+            &escrow_key,
+            false,
+            true,
+            &mut escrow_lamports,
+            &mut escrow_data,
+            &owner,
+            false,
+            0,
+        );
+        let recipient_info = AccountInfo::new(
+            &recipient_key,
+            true,
+            true,
+            &mut recipient_lamports,
+            &mut recipient_data,
+            &owner,
+            false,
+            0,
+        );
+
+        // Processors hold the escrow data while reimbursing
+        let escrow_data = escrow_info.data.borrow_mut();
+        reimburse_fee(escrow, &escrow_info, &recipient_info, fee)?;
+        drop(escrow_data);
+        let lamports = (escrow_info.lamports(), recipient_info.lamports());
+        Ok(lamports)
+    }
+
+    #[test]
+    fn test_settle_commission() {
+        let mut escrow = Escrow {
+            commission: 1_000,
+            ..Default::default()
+        };
+
+        // Commission is charged in basis points of the reward, rounded down
+        assert_eq!(
+            settle_commission(&mut escrow, &proof(999, [1; 32])),
+            Ok((999, 99))
+        );
+        assert_eq!(escrow.last_balance, 900);
+        assert_eq!(escrow.last_hash, [1; 32]);
+
+        // Commission is only charged on the reward mined since the commission was claimed
+        assert_eq!(
+            settle_commission(&mut escrow, &proof(1_900, [2; 32])),
+            Ok((1_000, 100))
+        );
+        assert_eq!(escrow.last_balance, 1_800);
+        assert_eq!(escrow.stats.total_commission, 199);
+        assert_eq!(
+            settle_commission(&mut escrow, &proof(1_800, [3; 32])),
+            Ok((0, 0))
+        );
+    }
+
+    #[test]
+    fn test_settle_commission_underflow() {
+        let mut escrow = Escrow {
+            commission: 1_000,
+            last_balance: 1_000,
+            ..Default::default()
+        };
+        assert_eq!(
+            settle_commission(&mut escrow, &proof(999, [1; 32])),
+            Err(RelayError::BalanceUnderflow.into())
+        );
+    }
+
+    #[test]
+    fn test_reimburse_fee() {
+        let mut escrow = Escrow {
+            max_fee_per_collect: 5_000,
+            epoch_fee_budget: 8_000,
+            ..Default::default()
+        };
+        let lamports = escrow_rent() + 1_000_000;
+
+        // Fees are paid to the recipient and recorded against the epoch budget
+        assert_eq!(
+            reimburse_fee_from(&mut escrow, lamports, 5_000),
+            Ok((lamports - 5_000, 5_000))
+        );
+        assert_eq!(escrow.fee_epoch_spent, 5_000);
+        assert_eq!(escrow.stats.total_reimbursed, 5_000);
+
+        // Fees above the cap are rejected
+        assert_eq!(
+            reimburse_fee_from(&mut escrow, lamports, 5_001),
+            Err(RelayError::FeeCapExceeded.into())
+        );
+    }
+
+    #[test]
+    fn test_reimburse_fee_epoch_budget() {
+        let mut escrow = Escrow {
+            max_fee_per_collect: 5_000,
+            epoch_fee_budget: 8_000,
+            ..Default::default()
+        };
+        let lamports = escrow_rent() + 1_000_000;
+        EPOCH.with(|epoch| epoch.set(1));
+        assert!(reimburse_fee_from(&mut escrow, lamports, 5_000).is_ok());
+        assert_eq!(
+            reimburse_fee_from(&mut escrow, lamports, 3_001),
+            Err(RelayError::EpochBudgetExceeded.into())
+        );
+        assert!(reimburse_fee_from(&mut escrow, lamports, 3_000).is_ok());
+
+        // The budget resets in the next epoch
+        EPOCH.with(|epoch| epoch.set(2));
+        assert!(reimburse_fee_from(&mut escrow, lamports, 5_000).is_ok());
+        assert_eq!(escrow.fee_epoch, 2);
+        assert_eq!(escrow.fee_epoch_spent, 5_000);
+    }
+
+    #[test]
+    fn test_reimburse_fee_rent_surplus() {
+        let mut escrow = Escrow {
+            max_fee_per_collect: 5_000,
+            epoch_fee_budget: 10_000,
+            ..Default::default()
+        };
+
+        // Only lamports above rent exemption may be paid out
+        let lamports = escrow_rent() + 4_999;
+        assert_eq!(
+            reimburse_fee_from(&mut escrow, lamports, 5_000),
+            Err(RelayError::InsufficientEscrowLamports.into())
+        );
+        assert_eq!(
+            reimburse_fee_from(&mut escrow, lamports, 4_999),
+            Ok((escrow_rent(), 4_999))
+        );
+    }
+
+    /// Builds a ComputeBudget instruction with the given tag and little-endian value.
+    fn compute_budget(tag: u8, value: &[u8]) -> Instruction {
+        Instruction {
+            program_id: COMPUTE_BUDGET_PROGRAM_ID,
+            accounts: vec![],
+            data: [&[tag], value].concat(),
+        }
+    }
+
+    #[test]
+    fn test_transaction_cost() {
+        let signer = Pubkey::new_unique();
+        let relayer = Pubkey::new_unique();
+        let ix = collect(signer, relayer, Pubkey::new_unique(), 0);
+
+        // Each distinct signer pays the base fee
+        assert_eq!(transaction_cost(&[ix.clone()]), LAMPORTS_PER_SIGNATURE);
+        assert_eq!(
+            transaction_cost(&[ix.clone(), ix.clone()]),
+            LAMPORTS_PER_SIGNATURE
+        );
+        let mut cosigned = ix.clone();
+        cosigned
+            .accounts
+            .push(AccountMeta::new_readonly(Pubkey::new_unique(), true));
+        assert_eq!(transaction_cost(&[cosigned]), 2 * LAMPORTS_PER_SIGNATURE);
+
+        // The priority fee is the compute unit price times the requested limit, rounded up
+        let price = compute_budget(3, &1_000_001u64.to_le_bytes());
+        let limit = compute_budget(2, &100_000u32.to_le_bytes());
+        assert_eq!(
+            transaction_cost(&[limit, price.clone(), ix.clone()]),
+            LAMPORTS_PER_SIGNATURE + 100_001
+        );
+
+        // Without a requested limit, each instruction is allotted the default limit
+        assert_eq!(
+            transaction_cost(&[price, ix.clone(), ix]),
+            LAMPORTS_PER_SIGNATURE + 400_001
+        );
+    }
+
+    #[test]
+    fn test_claimed_fees() {
+        let signer = Pubkey::new_unique();
+        let relayer = Pubkey::new_unique();
+        let escrows = [Pubkey::new_unique(), Pubkey::new_unique()];
+
+        // Batched collects claim the fee once per escrow
+        assert_eq!(
+            claimed_fees(&[
+                collect(signer, relayer, Pubkey::new_unique(), 1_000),
+                collect_many(signer, relayer, &escrows, 2_000),
+                compute_budget(3, &1u64.to_le_bytes()),
+            ]),
+            Ok(5_000)
+        );
+    }
+}
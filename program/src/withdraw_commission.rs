@@ -0,0 +1,72 @@
+use ore_api::consts::MINT_ADDRESS;
+use ore_relayer_api::{consts::*, error::RelayError, event::WithdrawCommissionEvent, loaders::*};
+use ore_utils::{spl::transfer_signed, AccountDeserialize};
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+};
+
+use crate::utils::assert_active;
+
+/// Withdraws the commission accrued in the relayer vault to the relayer beneficiary. Escrows
+/// claim commission into the vault as it is collected, so it is paid out with a single token
+/// transfer, however many escrows it was collected from.
+pub fn process_withdraw_commission<'a, 'info>(
+    accounts: &'a [AccountInfo<'info>],
+    _data: &[u8],
+) -> ProgramResult {
+    // Load accounts
+    let [signer, config_info, relayer_info, beneficiary_info, vault_info, token_program] = accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    load_signer(signer)?;
+    load_relay_config(config_info, false)?;
+    load_relayer(relayer_info, signer.key, true)?;
+    load_token_account(beneficiary_info, None, &MINT_ADDRESS, true)?;
+    load_token_account(vault_info, Some(relayer_info.key), &MINT_ADDRESS, true)?;
+    load_program(token_program, spl_token::id())?;
+
+    // Error if the program is paused
+    assert_active(config_info)?;
+
+    // Error if the relayer is paused
+    let mut relayer_data = relayer_info.data.borrow_mut();
+    let relayer = Relayer::try_from_bytes_mut(&mut relayer_data)?;
+    if relayer.is_paused() {
+        return Err(RelayError::RelayerPaused.into());
+    }
+
+    // Verify the beneficiary is the one registered by the relayer
+    if beneficiary_info.key.ne(&relayer.beneficiary) {
+        return Err(RelayError::InvalidBeneficiary.into());
+    }
+
+    // Reset the accrued commission
+    let amount = relayer.accrued_commission;
+    relayer.accrued_commission = 0;
+    let authority = relayer.authority;
+    let relayer_bump = relayer.bump as u8;
+    drop(relayer_data);
+
+    // Transfer the accrued commission from the vault to the beneficiary
+    if amount.gt(&0) {
+        transfer_signed(
+            relayer_info,
+            vault_info,
+            beneficiary_info,
+            token_program,
+            amount,
+            &[&[RELAYER, authority.as_ref(), &[relayer_bump]]],
+        )?;
+    }
+
+    // Log event
+    WithdrawCommissionEvent {
+        relayer: *relayer_info.key,
+        beneficiary: *beneficiary_info.key,
+        amount,
+    }
+    .log();
+
+    Ok(())
+}
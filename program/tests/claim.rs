@@ -0,0 +1,106 @@
+mod common;
+
+use common::*;
+use ore_api::consts::{MINT_ADDRESS, PROOF};
+use ore_relayer_api::{consts::*, error::RelayError, instruction::*, state::Escrow};
+use solana_program::pubkey::Pubkey;
+
+/// Sets up an escrow which has collected the given balance, and whose proof holds the given
+/// balance. Returns the escrow.
+fn setup(bank: &mut Bank, authority: Pubkey, last_balance: u64, proof_balance: u64) -> Pubkey {
+    let (escrow_address, _) =
+        Pubkey::find_program_address(&[ESCROW, authority.as_ref()], &ore_relayer_api::id());
+    let (proof_address, _) =
+        Pubkey::find_program_address(&[PROOF, escrow_address.as_ref()], &ore_api::id());
+    set_config(bank);
+    bank.set(
+        escrow_address,
+        ore_relayer_api::id(),
+        account_data(Escrow {
+            last_hash: [1; 32],
+            last_balance,
+            ..escrow_fixture(authority, Pubkey::new_unique())
+        }),
+    );
+    bank.set(
+        proof_address,
+        ore_api::id(),
+        account_data(proof_fixture(
+            escrow_address,
+            Pubkey::new_unique(),
+            proof_balance,
+            [2; 32],
+        )),
+    );
+    escrow_address
+}
+
+#[test]
+fn claim_is_limited_to_collected_balance() {
+    let mut bank = Bank::new();
+    let authority = Pubkey::new_unique();
+    let beneficiary = Pubkey::new_unique();
+    bank.set(
+        beneficiary,
+        spl_token::id(),
+        token_account_data(authority, 0),
+    );
+    let escrow_address = setup(&mut bank, authority, 1_000, 5_000);
+
+    // Rewards mined since the last collect may not be claimed
+    assert_eq!(
+        bank.process(&claim(authority, authority, beneficiary, 1_001)),
+        Err(RelayError::ClaimExceedsCollected.into())
+    );
+    assert!(take_invoked().is_empty());
+
+    // The collected balance may
+    assert_eq!(
+        bank.process(&claim(authority, authority, beneficiary, 1_000)),
+        Ok(())
+    );
+    assert_eq!(
+        take_invoked(),
+        vec![ore_api::instruction::claim(
+            escrow_address,
+            beneficiary,
+            1_000
+        )]
+    );
+    assert_eq!(bank.token_amount(&beneficiary), 1_000);
+    let escrow = bank.state::<Escrow>(&escrow_address);
+    assert_eq!(escrow.last_balance, 0);
+    assert_eq!(escrow.stats.total_claimed, 1_000);
+}
+
+#[test]
+fn stake_checks_balance_overflow() {
+    let mut bank = Bank::new();
+    let authority = Pubkey::new_unique();
+    let sender = Pubkey::new_unique();
+    bank.set(sender, spl_token::id(), token_account_data(authority, 10));
+    let escrow_address = setup(&mut bank, authority, u64::MAX - 1, u64::MAX - 1);
+    let escrow_tokens =
+        spl_associated_token_account::get_associated_token_address(&escrow_address, &MINT_ADDRESS);
+    bank.set(
+        escrow_tokens,
+        spl_token::id(),
+        token_account_data(escrow_address, 0),
+    );
+
+    // Stakes which overflow the collected balance are rejected
+    assert_eq!(
+        bank.process(&stake(authority, authority, sender, 2)),
+        Err(RelayError::BalanceOverflow.into())
+    );
+
+    // Others increment it
+    assert_eq!(
+        bank.process(&stake(authority, authority, sender, 1)),
+        Ok(())
+    );
+    assert_eq!(bank.token_amount(&sender), 9);
+    let escrow = bank.state::<Escrow>(&escrow_address);
+    assert_eq!(escrow.last_balance, u64::MAX);
+    assert_eq!(escrow.stats.total_staked, 1);
+}
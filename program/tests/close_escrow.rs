@@ -0,0 +1,270 @@
+mod common;
+
+use std::mem::size_of;
+
+use common::*;
+use ore_api::consts::{MINT_ADDRESS, PROOF};
+use ore_relayer_api::{
+    consts::*,
+    error::RelayError,
+    instruction::*,
+    state::{Config, Escrow, EscrowV1, Relayer},
+};
+use ore_utils::{AccountDeserialize, Discriminator};
+use solana_program::{pubkey::Pubkey, system_program};
+
+/// Sets up an escrow bound to a relayer, whose miner has mined the given proof balance.
+/// Returns the beneficiary and relayer.
+fn setup(
+    bank: &mut Bank,
+    authority: Pubkey,
+    escrow: Escrow,
+    proof_balance: u64,
+) -> (Pubkey, Pubkey) {
+    let beneficiary = Pubkey::new_unique();
+    let relayer_miner = Pubkey::new_unique();
+    let (escrow_address, escrow_bump) =
+        Pubkey::find_program_address(&[ESCROW, authority.as_ref()], &ore_relayer_api::id());
+    let (proof_address, _) =
+        Pubkey::find_program_address(&[PROOF, escrow_address.as_ref()], &ore_api::id());
+    let escrow_tokens_address =
+        spl_associated_token_account::get_associated_token_address(&escrow_address, &MINT_ADDRESS);
+    set_config(bank);
+    let relayer_address = bank.set_relayer(relayer_fixture(relayer_miner));
+    bank.set(
+        escrow_address,
+        ore_relayer_api::id(),
+        account_data(Escrow {
+            authority,
+            bump: escrow_bump as u64,
+            seed_authority: authority,
+            version: ESCROW_VERSION,
+            relayer: relayer_address,
+            commission: 1_000,
+            ..escrow
+        }),
+    );
+    bank.set(
+        proof_address,
+        ore_api::id(),
+        account_data(proof_fixture(
+            escrow_address,
+            relayer_miner,
+            proof_balance,
+            [2; 32],
+        )),
+    );
+    bank.set(
+        beneficiary,
+        spl_token::id(),
+        token_account_data(authority, 0),
+    );
+    bank.set(
+        escrow_tokens_address,
+        spl_token::id(),
+        token_account_data(escrow_address, 0),
+    );
+    (beneficiary, relayer_address)
+}
+
+/// Returns the lamports of the escrow and the proof and token accounts closed with it.
+fn closed_lamports(bank: &Bank, escrow_address: Pubkey) -> u64 {
+    let (proof_address, _) =
+        Pubkey::find_program_address(&[PROOF, escrow_address.as_ref()], &ore_api::id());
+    let escrow_tokens_address =
+        spl_associated_token_account::get_associated_token_address(&escrow_address, &MINT_ADDRESS);
+    bank.get(&escrow_address).lamports
+        + bank.get(&proof_address).lamports
+        + bank.get(&escrow_tokens_address).lamports
+}
+
+#[test]
+fn close_escrow_while_paused() {
+    let mut bank = Bank::new();
+    let authority = Pubkey::new_unique();
+    let (escrow_address, _) =
+        Pubkey::find_program_address(&[ESCROW, authority.as_ref()], &ore_relayer_api::id());
+
+    // The program is paused, and the relayer has mined a reward it has not collected
+    let escrow = Escrow {
+        last_hash: [1; 32],
+        ..Default::default()
+    };
+    let (beneficiary, relayer_address) = setup(&mut bank, authority, escrow, 1_000);
+    let mut config = bank.state::<Config>(&CONFIG_ADDRESS);
+    config.paused = 1;
+    bank.set(CONFIG_ADDRESS, ore_relayer_api::id(), account_data(config));
+    let relayer_miner = bank.state::<Relayer>(&relayer_address).miner;
+
+    // Collect is rejected while paused
+    assert_eq!(
+        bank.process(&collect(relayer_miner, relayer_address, authority, 0)),
+        Err(RelayError::ProgramPaused.into())
+    );
+
+    // Close settles commission inline and succeeds
+    assert_eq!(
+        bank.process(&close_escrow(
+            authority,
+            authority,
+            beneficiary,
+            relayer_address,
+            authority,
+        )),
+        Ok(())
+    );
+
+    // Commission is claimed into the relayer vault, and the rest of the proof balance to the user
+    let invoked = take_invoked();
+    assert_eq!(
+        invoked[0],
+        ore_api::instruction::claim(escrow_address, relayer_vault(relayer_address), 100)
+    );
+    assert_eq!(
+        invoked[1],
+        ore_api::instruction::claim(escrow_address, beneficiary, 900)
+    );
+    assert_eq!(bank.token_amount(&relayer_vault(relayer_address)), 100);
+    assert_eq!(
+        bank.state::<Relayer>(&relayer_address).accrued_commission,
+        100
+    );
+    assert_eq!(bank.token_amount(&beneficiary), 900);
+    let escrow = bank.get(&escrow_address);
+    assert_eq!(escrow.lamports, 0);
+    assert!(escrow.data.is_empty());
+    assert_eq!(bank.get(&authority).owner, system_program::id());
+}
+
+#[test]
+fn close_escrow_repays_rent_debt() {
+    let mut bank = Bank::new();
+    let authority = Pubkey::new_unique();
+    let rent_payer = Pubkey::new_unique();
+    let (escrow_address, _) =
+        Pubkey::find_program_address(&[ESCROW, authority.as_ref()], &ore_relayer_api::id());
+
+    // A sponsor funded the escrow rent
+    let escrow = Escrow {
+        last_hash: [2; 32],
+        rent_debt: 1_000,
+        rent_payer,
+        ..Default::default()
+    };
+    let (beneficiary, relayer_address) = setup(&mut bank, authority, escrow, 0);
+    let escrow_lamports = closed_lamports(&bank, escrow_address);
+
+    // The rent payer must be the sponsor
+    assert_eq!(
+        bank.process(&close_escrow(
+            authority,
+            authority,
+            beneficiary,
+            relayer_address,
+            authority,
+        )),
+        Err(RelayError::InvalidRentPayer.into())
+    );
+
+    // The sponsor is repaid the rent debt, and the user keeps the rest
+    assert_eq!(
+        bank.process(&close_escrow(
+            authority,
+            authority,
+            beneficiary,
+            relayer_address,
+            rent_payer,
+        )),
+        Ok(())
+    );
+    assert_eq!(bank.get(&rent_payer).lamports, 1_000);
+    assert_eq!(bank.get(&authority).lamports, escrow_lamports - 1_000);
+    assert_eq!(bank.get(&escrow_address).lamports, 0);
+}
+
+#[test]
+fn close_unbound_escrow() {
+    let mut bank = Bank::new();
+    let authority = Pubkey::new_unique();
+    let (escrow_address, _) =
+        Pubkey::find_program_address(&[ESCROW, authority.as_ref()], &ore_relayer_api::id());
+    let (beneficiary, relayer_address) = setup(&mut bank, authority, Escrow::default(), 1_000);
+
+    // The relayer was revoked, leaving the escrow unbound with a reward it did not collect
+    let mut escrow = bank.state::<Escrow>(&escrow_address);
+    escrow.relayer = Pubkey::default();
+    escrow.commission = 0;
+    bank.set(escrow_address, ore_relayer_api::id(), account_data(escrow));
+
+    // Close passes the default pubkey as the relayer, and the user keeps the full reward
+    assert_eq!(
+        bank.process(&close_escrow(
+            authority,
+            authority,
+            beneficiary,
+            Pubkey::default(),
+            authority,
+        )),
+        Ok(())
+    );
+    assert_eq!(bank.token_amount(&beneficiary), 1_000);
+    assert_eq!(bank.token_amount(&relayer_vault(relayer_address)), 0);
+}
+
+#[test]
+fn close_legacy_escrow() {
+    let mut bank = Bank::new();
+    let authority = Pubkey::new_unique();
+    let (escrow_address, escrow_bump) =
+        Pubkey::find_program_address(&[ESCROW, authority.as_ref()], &ore_relayer_api::id());
+    let (beneficiary, relayer_address) = setup(&mut bank, authority, Escrow::default(), 1_000);
+
+    // Replace the escrow with a legacy one, with a reward it has not collected
+    let mut escrow_data = vec![0; 8 + size_of::<EscrowV1>()];
+    escrow_data[0] = EscrowV1::discriminator();
+    *EscrowV1::try_from_bytes_mut(&mut escrow_data).unwrap() = EscrowV1 {
+        authority,
+        bump: escrow_bump as u64,
+        last_hash: [1; 32],
+        last_balance: 900,
+    };
+    bank.set(escrow_address, ore_relayer_api::id(), escrow_data.clone());
+
+    // Legacy escrows must be migrated to settle the commission on an uncollected reward
+    assert_eq!(
+        bank.process(&close_escrow(
+            authority,
+            authority,
+            beneficiary,
+            relayer_address,
+            authority,
+        )),
+        Err(RelayError::EscrowOutdated.into())
+    );
+
+    // Once collected, the legacy escrow closes without commission or rent debt
+    EscrowV1::try_from_bytes_mut(&mut escrow_data)
+        .unwrap()
+        .last_hash = [2; 32];
+    bank.set(escrow_address, ore_relayer_api::id(), escrow_data);
+    let escrow_lamports = closed_lamports(&bank, escrow_address);
+    assert_eq!(
+        bank.process(&close_escrow(
+            authority,
+            authority,
+            beneficiary,
+            relayer_address,
+            authority,
+        )),
+        Ok(())
+    );
+    let invoked = take_invoked();
+    assert_eq!(
+        invoked[0],
+        ore_api::instruction::claim(escrow_address, beneficiary, 1_000)
+    );
+    assert_eq!(invoked[1], ore_api::instruction::close(escrow_address));
+    assert_eq!(bank.token_amount(&beneficiary), 1_000);
+    assert_eq!(bank.get(&authority).lamports, escrow_lamports);
+    assert_eq!(bank.get(&escrow_address).lamports, 0);
+}
@@ -0,0 +1,300 @@
+mod common;
+
+use common::*;
+use ore_api::{consts::PROOF, state::Proof};
+use ore_relayer_api::{
+    consts::*,
+    error::RelayError,
+    instruction::*,
+    state::{Escrow, Relayer},
+};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    rent::Rent,
+};
+
+/// Sets up a relayer and an escrow bound to it at the given commission rate, whose proof has
+/// mined the given balance since it was opened. Returns the relayer, the escrow and the proof.
+fn setup(
+    bank: &mut Bank,
+    relayer_miner: Pubkey,
+    authority: Pubkey,
+    commission: u64,
+    proof_balance: u64,
+) -> (Pubkey, Pubkey, Pubkey) {
+    let (escrow_address, _) =
+        Pubkey::find_program_address(&[ESCROW, authority.as_ref()], &ore_relayer_api::id());
+    let (proof_address, _) =
+        Pubkey::find_program_address(&[PROOF, escrow_address.as_ref()], &ore_api::id());
+    set_config(bank);
+    let relayer_address = bank.set_relayer(Relayer {
+        commission,
+        ..relayer_fixture(relayer_miner)
+    });
+    bank.set(
+        escrow_address,
+        ore_relayer_api::id(),
+        account_data(Escrow {
+            last_hash: [1; 32],
+            commission,
+            ..escrow_fixture(authority, relayer_address)
+        }),
+    );
+    bank.set(
+        proof_address,
+        ore_api::id(),
+        account_data(proof_fixture(
+            escrow_address,
+            relayer_miner,
+            proof_balance,
+            [2; 32],
+        )),
+    );
+    (relayer_address, escrow_address, proof_address)
+}
+
+/// Credits a reward and a new hash to the proof.
+fn mine_hash(bank: &mut Bank, proof_address: Pubkey, reward: u64, hash: [u8; 32]) {
+    let mut proof = bank.state::<Proof>(&proof_address);
+    proof.balance += reward;
+    proof.last_hash = hash;
+    bank.set(proof_address, ore_api::id(), account_data(proof));
+}
+
+#[test]
+fn collect_charges_commission_in_bps() {
+    let mut bank = Bank::new();
+    let relayer_miner = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let (relayer_address, escrow_address, proof_address) =
+        setup(&mut bank, relayer_miner, authority, 250, 10_000);
+
+    // Commission is 2.5% of the reward, and is claimed into the relayer vault
+    assert_eq!(
+        bank.process(&collect(relayer_miner, relayer_address, authority, 0)),
+        Ok(())
+    );
+    let escrow = bank.state::<Escrow>(&escrow_address);
+    assert_eq!(bank.token_amount(&relayer_vault(relayer_address)), 250);
+    assert_eq!(escrow.last_balance, 9_750);
+    assert_eq!(escrow.last_hash, [2; 32]);
+    assert_eq!(
+        bank.state::<Relayer>(&relayer_address).accrued_commission,
+        250
+    );
+
+    // Only the reward mined since the last collect is charged
+    mine_hash(&mut bank, proof_address, 1_000_000, [3; 32]);
+    assert_eq!(
+        bank.process(&collect(relayer_miner, relayer_address, authority, 0)),
+        Ok(())
+    );
+    let escrow = bank.state::<Escrow>(&escrow_address);
+    assert_eq!(
+        bank.token_amount(&relayer_vault(relayer_address)),
+        250 + 25_000
+    );
+    assert_eq!(escrow.last_balance, 1_010_000 - 25_250);
+
+    // Rewards too small to owe commission owe none
+    mine_hash(&mut bank, proof_address, 39, [4; 32]);
+    assert_eq!(
+        bank.process(&collect(relayer_miner, relayer_address, authority, 0)),
+        Ok(())
+    );
+    let escrow = bank.state::<Escrow>(&escrow_address);
+    assert_eq!(bank.token_amount(&relayer_vault(relayer_address)), 25_250);
+    assert_eq!(escrow.last_balance, 1_010_039 - 25_250);
+}
+
+/// Funds an escrow with the given lamports above rent exemption.
+fn fund_escrow(bank: &mut Bank, escrow_address: Pubkey, surplus: u64) {
+    let rent = Rent::default().minimum_balance(bank.get(&escrow_address).data.len());
+    bank.set_lamports(escrow_address, rent + surplus);
+}
+
+#[test]
+fn collect_enforces_fee_cap() {
+    let mut bank = Bank::new();
+    let relayer_miner = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let (relayer_address, escrow_address, _) =
+        setup(&mut bank, relayer_miner, authority, 1_000, 10_000);
+    fund_escrow(&mut bank, escrow_address, 1_000_000);
+
+    // The user caps the fee per collect
+    assert_eq!(
+        bank.process(&configure_escrow(
+            authority,
+            authority,
+            1_000,
+            DEFAULT_EPOCH_FEE_BUDGET
+        )),
+        Ok(())
+    );
+    assert_eq!(
+        bank.state::<Escrow>(&escrow_address).max_fee_per_collect,
+        1_000
+    );
+
+    // Fees above the cap are rejected
+    assert_eq!(
+        bank.process(&collect(relayer_miner, relayer_address, authority, 1_001)),
+        Err(RelayError::FeeCapExceeded.into())
+    );
+
+    // Fees up to the cap are reimbursed
+    let escrow_lamports = bank.get(&escrow_address).lamports;
+    assert_eq!(
+        bank.process(&collect(relayer_miner, relayer_address, authority, 1_000)),
+        Ok(())
+    );
+    assert_eq!(bank.get(&escrow_address).lamports, escrow_lamports - 1_000);
+    assert_eq!(bank.get(&relayer_miner).lamports, 1_000);
+}
+
+#[test]
+fn collect_enforces_epoch_fee_budget() {
+    let mut bank = Bank::new();
+    let relayer_miner = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let (relayer_address, escrow_address, proof_address) =
+        setup(&mut bank, relayer_miner, authority, 1_000, 10_000);
+    fund_escrow(&mut bank, escrow_address, 1_000_000);
+    assert_eq!(
+        bank.process(&configure_escrow(authority, authority, 5_000, 7_000)),
+        Ok(())
+    );
+
+    // Fees are reimbursed up to the epoch budget
+    set_epoch(1);
+    assert_eq!(
+        bank.process(&collect(relayer_miner, relayer_address, authority, 4_000)),
+        Ok(())
+    );
+    mine_hash(&mut bank, proof_address, 1_000, [3; 32]);
+    assert_eq!(
+        bank.process(&collect(relayer_miner, relayer_address, authority, 3_001)),
+        Err(RelayError::EpochBudgetExceeded.into())
+    );
+    assert_eq!(
+        bank.process(&collect(relayer_miner, relayer_address, authority, 3_000)),
+        Ok(())
+    );
+    let escrow = bank.state::<Escrow>(&escrow_address);
+    assert_eq!(escrow.fee_epoch, 1);
+    assert_eq!(escrow.fee_epoch_spent, 7_000);
+
+    // The budget resets in the next epoch
+    set_epoch(2);
+    mine_hash(&mut bank, proof_address, 1_000, [4; 32]);
+    assert_eq!(
+        bank.process(&collect(relayer_miner, relayer_address, authority, 5_000)),
+        Ok(())
+    );
+    let escrow = bank.state::<Escrow>(&escrow_address);
+    assert_eq!(escrow.fee_epoch, 2);
+    assert_eq!(escrow.fee_epoch_spent, 5_000);
+    assert_eq!(bank.get(&relayer_miner).lamports, 12_000);
+}
+
+#[test]
+fn collect_guards_rent_exemption() {
+    let mut bank = Bank::new();
+    let relayer_miner = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let (relayer_address, escrow_address, _) =
+        setup(&mut bank, relayer_miner, authority, 1_000, 10_000);
+    fund_escrow(&mut bank, escrow_address, 2_000);
+
+    // Fees may not dip into the rent exempt balance
+    let escrow_lamports = bank.get(&escrow_address).lamports;
+    assert_eq!(
+        bank.process(&collect(relayer_miner, relayer_address, authority, 2_001)),
+        Err(RelayError::InsufficientEscrowLamports.into())
+    );
+    assert_eq!(bank.get(&escrow_address).lamports, escrow_lamports);
+
+    // The surplus above rent exemption may be spent
+    assert_eq!(
+        bank.process(&collect(relayer_miner, relayer_address, authority, 2_000)),
+        Ok(())
+    );
+    assert_eq!(bank.get(&escrow_address).lamports, escrow_lamports - 2_000);
+}
+
+#[test]
+fn collect_rejects_fee_claimed_by_cpi() {
+    let mut bank = Bank::new();
+    let relayer_miner = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let (relayer_address, escrow_address, _) =
+        setup(&mut bank, relayer_miner, authority, 1_000, 10_000);
+    fund_escrow(&mut bank, escrow_address, 1_000_000);
+
+    // A wrapper program invokes the collect, hiding its fee from the instructions sysvar
+    let ix = collect(
+        relayer_miner,
+        relayer_address,
+        authority,
+        2 * LAMPORTS_PER_SIGNATURE,
+    );
+    let wrapper = Instruction {
+        program_id: Pubkey::new_unique(),
+        accounts: vec![AccountMeta::new(relayer_miner, true)],
+        data: vec![],
+    };
+    assert_eq!(
+        bank.process_cpi(&wrapper, &ix),
+        Err(RelayError::FeeClaimedByCpi.into())
+    );
+    assert_eq!(bank.get(&relayer_miner).lamports, 0);
+
+    // The same collect at the top level is bounded by the cost of its transaction
+    assert_eq!(
+        bank.process(&ix),
+        Err(RelayError::FeeExceedsTransactionCost.into())
+    );
+}
+
+#[test]
+fn collect_errors_decode_to_relay_errors() {
+    let mut bank = Bank::new();
+    let relayer_miner = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let (relayer_address, _, _) = setup(&mut bank, relayer_miner, authority, 1_000, 10_000);
+
+    // Only the relayer miner may collect
+    let err = bank
+        .process(&collect(
+            Pubkey::new_unique(),
+            relayer_address,
+            authority,
+            0,
+        ))
+        .unwrap_err();
+    assert_eq!(
+        RelayError::from_program_error(&err),
+        Some(RelayError::UnauthorizedRelayer)
+    );
+
+    // Commission is collected once per hash
+    assert_eq!(
+        bank.process(&collect(relayer_miner, relayer_address, authority, 0)),
+        Ok(())
+    );
+    let err = bank
+        .process(&collect(relayer_miner, relayer_address, authority, 0))
+        .unwrap_err();
+    assert_eq!(
+        RelayError::from_program_error(&err),
+        Some(RelayError::DuplicateCollect)
+    );
+
+    // Builtin program errors are not relay errors
+    let mut ix = collect(relayer_miner, relayer_address, authority, 0);
+    ix.accounts.pop();
+    let err = bank.process(&ix).unwrap_err();
+    assert_eq!(RelayError::from_program_error(&err), None);
+}
@@ -0,0 +1,121 @@
+mod common;
+
+use common::*;
+use ore_api::consts::PROOF;
+use ore_relayer_api::{
+    consts::*,
+    error::RelayError,
+    instruction::*,
+    state::{Escrow, Relayer},
+};
+use solana_program::pubkey::Pubkey;
+
+/// Sets up an escrow bound to the relayer, whose proof has mined the given balance and hash.
+/// Returns the escrow address.
+fn setup_escrow(
+    bank: &mut Bank,
+    relayer_address: Pubkey,
+    relayer_miner: Pubkey,
+    proof_balance: u64,
+    proof_hash: [u8; 32],
+) -> Pubkey {
+    let authority = Pubkey::new_unique();
+    let (escrow_address, _) =
+        Pubkey::find_program_address(&[ESCROW, authority.as_ref()], &ore_relayer_api::id());
+    let (proof_address, _) =
+        Pubkey::find_program_address(&[PROOF, escrow_address.as_ref()], &ore_api::id());
+    bank.set(
+        escrow_address,
+        ore_relayer_api::id(),
+        account_data(Escrow {
+            last_hash: [1; 32],
+            ..escrow_fixture(authority, relayer_address)
+        }),
+    );
+    bank.set(
+        proof_address,
+        ore_api::id(),
+        account_data(proof_fixture(
+            escrow_address,
+            relayer_miner,
+            proof_balance,
+            proof_hash,
+        )),
+    );
+    escrow_address
+}
+
+#[test]
+fn collect_many_skips_unchanged_escrows() {
+    let mut bank = Bank::new();
+    let relayer_authority = Pubkey::new_unique();
+    let relayer_miner = Pubkey::new_unique();
+    set_config(&mut bank);
+    let relayer_address = bank.set_relayer(Relayer {
+        authority: relayer_authority,
+        ..relayer_fixture(relayer_miner)
+    });
+    let mined = setup_escrow(&mut bank, relayer_address, relayer_miner, 1_000, [2; 32]);
+    let unchanged = setup_escrow(&mut bank, relayer_address, relayer_miner, 0, [1; 32]);
+    let also_mined = setup_escrow(&mut bank, relayer_address, relayer_miner, 2_000, [3; 32]);
+
+    // Only the relayer miner may collect
+    assert_eq!(
+        bank.process(&collect_many(
+            relayer_authority,
+            relayer_address,
+            &[mined, unchanged, also_mined],
+            0
+        )),
+        Err(RelayError::UnauthorizedRelayer.into())
+    );
+
+    // Fees claimed across the batch may not exceed the cost of the transaction
+    assert_eq!(
+        bank.process(&collect_many(
+            relayer_miner,
+            relayer_address,
+            &[mined, unchanged, also_mined],
+            LAMPORTS_PER_SIGNATURE / 2
+        )),
+        Err(RelayError::FeeExceedsTransactionCost.into())
+    );
+
+    // Escrows with a new hash are collected, and the unchanged escrow is skipped
+    set_slot(100);
+    assert_eq!(
+        bank.process(&collect_many(
+            relayer_miner,
+            relayer_address,
+            &[mined, unchanged, also_mined],
+            0
+        )),
+        Ok(())
+    );
+    let escrow = bank.state::<Escrow>(&mined);
+    assert_eq!(escrow.last_hash, [2; 32]);
+    assert_eq!(escrow.last_balance, 900);
+    assert_eq!(escrow.stats.collects, 1);
+    let escrow = bank.state::<Escrow>(&unchanged);
+    assert_eq!(escrow.last_hash, [1; 32]);
+    assert_eq!(escrow.stats.collects, 0);
+    let escrow = bank.state::<Escrow>(&also_mined);
+    assert_eq!(escrow.last_hash, [3; 32]);
+    assert_eq!(escrow.last_balance, 1_800);
+    let relayer = bank.state::<Relayer>(&relayer_address);
+    assert_eq!(relayer.accrued_commission, 300);
+    assert_eq!(bank.token_amount(&relayer_vault(relayer_address)), 300);
+
+    // Escrows of another relayer fail the batch
+    let other = setup_escrow(
+        &mut bank,
+        Pubkey::new_unique(),
+        relayer_miner,
+        1_000,
+        [2; 32],
+    );
+    assert_eq!(
+        bank.process(&collect_many(relayer_miner, relayer_address, &[other], 0)),
+        Err(RelayError::UnauthorizedRelayer.into())
+    );
+}
@@ -0,0 +1,685 @@
+#![allow(dead_code)]
+
+use std::{cell::RefCell, collections::HashMap, mem::size_of, sync::Once};
+
+use ore_api::{
+    consts::{MINT_ADDRESS, TOKEN_DECIMALS, TREASURY_ADDRESS, TREASURY_TOKENS_ADDRESS},
+    instruction::OreInstruction,
+    state::{Proof, Treasury},
+};
+use ore_relayer_api::{
+    consts::{
+        CONFIG_ADDRESS, DEFAULT_EPOCH_FEE_BUDGET, DEFAULT_MAX_FEE_PER_COLLECT, ESCROW,
+        ESCROW_VERSION, MIN_RELAYER_BOND, RELAYER,
+    },
+    state::{Config, Escrow, Relayer},
+};
+use ore_utils::{AccountDeserialize, Discriminator};
+use solana_program::{
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::{
+        deserialize, ProgramResult, MAX_PERMITTED_DATA_INCREASE, NON_DUP_MARKER, SUCCESS,
+    },
+    instruction::{Instruction, TRANSACTION_LEVEL_STACK_HEIGHT},
+    program_error::ProgramError,
+    program_option::COption,
+    program_pack::Pack,
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+    pubkey,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_program,
+    sysvar::{
+        self,
+        instructions::{construct_instructions_data, BorrowedAccountMeta, BorrowedInstruction},
+    },
+};
+
+/// The ORE held by the treasury token account, from which proof balances are claimed.
+pub const TREASURY_SUPPLY: u64 = 1_000_000_000_000;
+
+/// The owner of builtin programs.
+const NATIVE_LOADER_ID: Pubkey = pubkey!("NativeLoader1111111111111111111111111111111");
+
+thread_local! {
+    /// Instructions invoked by the program under test on this thread.
+    static INVOKED: RefCell<Vec<Instruction>> = const { RefCell::new(vec![]) };
+
+    /// The clock served to the program under test on this thread.
+    static CLOCK: RefCell<Clock> = RefCell::new(Clock::default());
+
+    /// The reward and hash credited to the proof by ORE mine invocations on this thread.
+    static MINE_RESULT: RefCell<(u64, [u8; 32])> = const { RefCell::new((0, [0; 32])) };
+
+    /// The invocation stack height of the program under test on this thread.
+    static STACK_HEIGHT: RefCell<usize> = const { RefCell::new(TRANSACTION_LEVEL_STACK_HEIGHT) };
+}
+
+/// Emulates the programs invoked by the program under test, records the invocations, and serves
+/// sysvars.
+struct TestStubs;
+
+impl SyscallStubs for TestStubs {
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        INVOKED.with(|invoked| invoked.borrow_mut().push(instruction.clone()));
+        let find = |key: &Pubkey| account_infos.iter().find(|info| info.key.eq(key)).unwrap();
+
+        // Verify signers, as the runtime would. Program addresses sign with their seeds.
+        let signers = signers_seeds
+            .iter()
+            .map(|seeds| Pubkey::create_program_address(seeds, &ore_relayer_api::id()))
+            .collect::<Result<Vec<_>, _>>()
+            .or(Err(ProgramError::InvalidSeeds))?;
+        for meta in instruction.accounts.iter().filter(|meta| meta.is_signer) {
+            if !find(&meta.pubkey).is_signer && !signers.contains(&meta.pubkey) {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+        }
+
+        // Move lamports and create accounts for the system program
+        if instruction.program_id.eq(&system_program::id()) {
+            let data = &instruction.data;
+            let tag = u32::from_le_bytes(data[..4].try_into().unwrap());
+            let lamports = u64::from_le_bytes(data[4..12].try_into().unwrap());
+            let from = find(&instruction.accounts[0].pubkey);
+            let to = find(&instruction.accounts[1].pubkey);
+            let balance = from.lamports().checked_sub(lamports);
+            **from.lamports.borrow_mut() = balance.ok_or(ProgramError::InsufficientFunds)?;
+            **to.lamports.borrow_mut() += lamports;
+            if tag.eq(&0) {
+                let space = u64::from_le_bytes(data[12..20].try_into().unwrap());
+                let owner = Pubkey::new_from_array(data[20..52].try_into().unwrap());
+                to.realloc(space as usize, true)?;
+                to.assign(&owner);
+            }
+            return Ok(());
+        }
+
+        // Create associated token accounts
+        if instruction
+            .program_id
+            .eq(&spl_associated_token_account::id())
+        {
+            let payer = find(&instruction.accounts[0].pubkey);
+            let owner = instruction.accounts[2].pubkey;
+            let tokens = find(&instruction.accounts[1].pubkey);
+            create_account(
+                payer,
+                tokens,
+                &spl_token::id(),
+                &token_account_data(owner, 0),
+            )?;
+            return Ok(());
+        }
+
+        // Transfer tokens and close token accounts
+        if instruction.program_id.eq(&spl_token::id()) {
+            let accounts = &instruction.accounts;
+            match spl_token::instruction::TokenInstruction::unpack(&instruction.data)? {
+                spl_token::instruction::TokenInstruction::Transfer { amount } => {
+                    assert_token_owner(find(&accounts[0].pubkey), &accounts[2].pubkey)?;
+                    debit_tokens(find(&accounts[0].pubkey), amount)?;
+                    credit_tokens(find(&accounts[1].pubkey), amount)?;
+                }
+                spl_token::instruction::TokenInstruction::CloseAccount => {
+                    let tokens = find(&accounts[0].pubkey);
+                    assert_token_owner(tokens, &accounts[2].pubkey)?;
+                    if token_amount(tokens)?.gt(&0) {
+                        return Err(ProgramError::InvalidAccountData);
+                    }
+                    close_account(tokens, find(&accounts[1].pubkey))?;
+                }
+                _ => {}
+            }
+            return Ok(());
+        }
+
+        // Emulate the ORE program
+        if instruction.program_id.ne(&ore_api::id()) {
+            return Ok(());
+        }
+        let accounts = &instruction.accounts;
+        let amount = || u64::from_le_bytes(instruction.data[1..9].try_into().unwrap());
+        match OreInstruction::try_from(instruction.data[0]).unwrap() {
+            // Open the proof
+            OreInstruction::Open => {
+                let payer = find(&accounts[2].pubkey);
+                let proof_info = find(&accounts[3].pubkey);
+                let proof = Proof {
+                    authority: accounts[0].pubkey,
+                    balance: 0,
+                    challenge: [0; 32],
+                    last_hash: [0; 32],
+                    last_hash_at: 0,
+                    last_stake_at: 0,
+                    miner: accounts[1].pubkey,
+                    total_hashes: 0,
+                    total_rewards: 0,
+                };
+                create_account(payer, proof_info, &ore_api::id(), &account_data(proof))?;
+            }
+
+            // Credit the mine result to the proof
+            OreInstruction::Mine => {
+                let (reward, hash) = MINE_RESULT.with(|result| *result.borrow());
+                let proof_info = find(&accounts[3].pubkey);
+                let mut proof_data = proof_info.data.borrow_mut();
+                let proof = Proof::try_from_bytes_mut(&mut proof_data)?;
+                proof.balance += reward;
+                proof.last_hash = hash;
+            }
+
+            // Claim from the proof balance to the beneficiary
+            OreInstruction::Claim => {
+                let amount = amount();
+                let proof_info = find(&accounts[2].pubkey);
+                let mut proof_data = proof_info.data.borrow_mut();
+                let proof = Proof::try_from_bytes_mut(&mut proof_data)?;
+                if proof.authority.ne(&accounts[0].pubkey) {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+                proof.balance = proof
+                    .balance
+                    .checked_sub(amount)
+                    .ok_or(ProgramError::InsufficientFunds)?;
+                debit_tokens(find(&accounts[4].pubkey), amount)?;
+                credit_tokens(find(&accounts[1].pubkey), amount)?;
+            }
+
+            // Stake from the sender to the proof balance
+            OreInstruction::Stake => {
+                let amount = amount();
+                let proof_info = find(&accounts[1].pubkey);
+                let mut proof_data = proof_info.data.borrow_mut();
+                let proof = Proof::try_from_bytes_mut(&mut proof_data)?;
+                if proof.authority.ne(&accounts[0].pubkey) {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+                assert_token_owner(find(&accounts[2].pubkey), &accounts[0].pubkey)?;
+                debit_tokens(find(&accounts[2].pubkey), amount)?;
+                credit_tokens(find(&accounts[3].pubkey), amount)?;
+                proof.balance += amount;
+            }
+
+            // Change the miner of the proof
+            OreInstruction::Update => {
+                let proof_info = find(&accounts[2].pubkey);
+                let mut proof_data = proof_info.data.borrow_mut();
+                let proof = Proof::try_from_bytes_mut(&mut proof_data)?;
+                if proof.authority.ne(&accounts[0].pubkey) {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+                proof.miner = accounts[1].pubkey;
+            }
+
+            // Close the proof, which must hold no balance, returning rent to the signer
+            OreInstruction::Close => {
+                let proof_info = find(&accounts[1].pubkey);
+                let proof_data = proof_info.data.borrow();
+                let proof = Proof::try_from_bytes(&proof_data)?;
+                if proof.authority.ne(&accounts[0].pubkey) || proof.balance.gt(&0) {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+                drop(proof_data);
+                close_account(proof_info, find(&accounts[0].pubkey))?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = CLOCK.with(|clock| clock.borrow().clone());
+        unsafe { *(var_addr as *mut Clock) = clock };
+        SUCCESS
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        SUCCESS
+    }
+
+    fn sol_get_stack_height(&self) -> u64 {
+        STACK_HEIGHT.with(|height| *height.borrow() as u64)
+    }
+}
+
+/// Creates an account with the given data, funded for rent exemption by the payer.
+fn create_account(
+    payer: &AccountInfo,
+    info: &AccountInfo,
+    owner: &Pubkey,
+    data: &[u8],
+) -> ProgramResult {
+    let lamports = Rent::default().minimum_balance(data.len());
+    let balance = payer.lamports().checked_sub(lamports);
+    **payer.lamports.borrow_mut() = balance.ok_or(ProgramError::InsufficientFunds)?;
+    **info.lamports.borrow_mut() += lamports;
+    info.realloc(data.len(), true)?;
+    info.data.borrow_mut().copy_from_slice(data);
+    info.assign(owner);
+    Ok(())
+}
+
+/// Closes an account, returning its lamports to the destination.
+fn close_account(info: &AccountInfo, destination: &AccountInfo) -> ProgramResult {
+    **destination.lamports.borrow_mut() += info.lamports();
+    **info.lamports.borrow_mut() = 0;
+    info.realloc(0, true)?;
+    info.assign(&system_program::id());
+    Ok(())
+}
+
+/// Errors if the token account is not owned by the given owner.
+fn assert_token_owner(info: &AccountInfo, owner: &Pubkey) -> ProgramResult {
+    let tokens = spl_token::state::Account::unpack(&info.data.borrow())?;
+    if tokens.owner.ne(owner) {
+        return Err(ProgramError::IllegalOwner);
+    }
+    Ok(())
+}
+
+fn token_amount(info: &AccountInfo) -> Result<u64, ProgramError> {
+    Ok(spl_token::state::Account::unpack(&info.data.borrow())?.amount)
+}
+
+fn debit_tokens(info: &AccountInfo, amount: u64) -> ProgramResult {
+    let mut tokens = spl_token::state::Account::unpack(&info.data.borrow())?;
+    tokens.amount = tokens
+        .amount
+        .checked_sub(amount)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    tokens.pack_into_slice(&mut info.data.borrow_mut());
+    Ok(())
+}
+
+fn credit_tokens(info: &AccountInfo, amount: u64) -> ProgramResult {
+    let mut tokens = spl_token::state::Account::unpack(&info.data.borrow())?;
+    tokens.amount += amount;
+    tokens.pack_into_slice(&mut info.data.borrow_mut());
+    Ok(())
+}
+
+/// Returns the instructions invoked by the program on this thread, and clears them.
+pub fn take_invoked() -> Vec<Instruction> {
+    INVOKED.with(|invoked| invoked.take())
+}
+
+/// Sets the reward and hash the next ORE mine invocations credit to the proof on this thread.
+pub fn set_mine_result(reward: u64, hash: [u8; 32]) {
+    MINE_RESULT.with(|result| *result.borrow_mut() = (reward, hash));
+}
+
+/// Sets the slot served by the clock sysvar on this thread.
+pub fn set_slot(slot: u64) {
+    CLOCK.with(|clock| clock.borrow_mut().slot = slot);
+}
+
+/// Sets the epoch served by the clock sysvar on this thread.
+pub fn set_epoch(epoch: u64) {
+    CLOCK.with(|clock| clock.borrow_mut().epoch = epoch);
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Account {
+    pub owner: Pubkey,
+    pub lamports: u64,
+    pub data: Vec<u8>,
+    pub executable: bool,
+}
+
+/// Accounts the program under test runs against. Accounts which were never set are empty and
+/// owned by the system program.
+#[derive(Default)]
+pub struct Bank {
+    accounts: HashMap<Pubkey, Account>,
+}
+
+impl Bank {
+    pub fn new() -> Self {
+        static STUBS: Once = Once::new();
+        STUBS.call_once(|| {
+            set_syscall_stubs(Box::new(TestStubs));
+        });
+        let mut bank = Self::default();
+        for program in [
+            ore_api::id(),
+            system_program::id(),
+            spl_token::id(),
+            spl_associated_token_account::id(),
+        ] {
+            bank.set_program(program);
+        }
+        bank.set(TREASURY_ADDRESS, ore_api::id(), account_data(Treasury {}));
+        bank.set(MINT_ADDRESS, spl_token::id(), mint_data());
+        bank.set(sysvar::slot_hashes::id(), sysvar::id(), vec![0; 8]);
+        bank.set(
+            TREASURY_TOKENS_ADDRESS,
+            spl_token::id(),
+            token_account_data(TREASURY_ADDRESS, TREASURY_SUPPLY),
+        );
+        bank
+    }
+
+    pub fn set(&mut self, key: Pubkey, owner: Pubkey, data: Vec<u8>) {
+        let lamports = Rent::default().minimum_balance(data.len());
+        self.accounts.insert(
+            key,
+            Account {
+                owner,
+                lamports,
+                data,
+                executable: false,
+            },
+        );
+    }
+
+    /// Sets the relayer account of the relayer's authority, a vault holding its accrued
+    /// commission, and its beneficiary token account if missing. Returns the relayer address.
+    pub fn set_relayer(&mut self, relayer: Relayer) -> Pubkey {
+        let (address, bump) = Pubkey::find_program_address(
+            &[RELAYER, relayer.authority.as_ref()],
+            &ore_relayer_api::id(),
+        );
+        self.set(
+            address,
+            ore_relayer_api::id(),
+            account_data(Relayer {
+                bump: bump as u64,
+                ..relayer
+            }),
+        );
+        self.set(
+            relayer_vault(address),
+            spl_token::id(),
+            token_account_data(address, relayer.accrued_commission),
+        );
+        if !self.accounts.contains_key(&relayer.beneficiary) {
+            self.set(
+                relayer.beneficiary,
+                spl_token::id(),
+                token_account_data(relayer.authority, 0),
+            );
+        }
+        address
+    }
+
+    pub fn set_program(&mut self, key: Pubkey) {
+        // Builtin programs are owned by the native loader and hold their name as data
+        self.accounts.insert(
+            key,
+            Account {
+                owner: NATIVE_LOADER_ID,
+                lamports: 1,
+                data: key.to_string().into_bytes(),
+                executable: true,
+            },
+        );
+    }
+
+    pub fn set_lamports(&mut self, key: Pubkey, lamports: u64) {
+        self.accounts.entry(key).or_default().lamports = lamports;
+    }
+
+    pub fn get(&self, key: &Pubkey) -> Account {
+        self.accounts.get(key).cloned().unwrap_or(Account {
+            owner: system_program::id(),
+            ..Account::default()
+        })
+    }
+
+    /// Returns the token amount held by a token account.
+    pub fn token_amount(&self, key: &Pubkey) -> u64 {
+        spl_token::state::Account::unpack(&self.get(key).data)
+            .unwrap()
+            .amount
+    }
+
+    /// Parses an account of the relay program.
+    pub fn state<T: Copy + Discriminator + AccountDeserialize>(&self, key: &Pubkey) -> T {
+        *T::try_from_bytes(&self.get(key).data).unwrap()
+    }
+
+    /// Processes the relay program instructions of a transaction in order, as the runtime would.
+    /// Instructions of other programs are assumed to succeed. Changes are discarded on error.
+    pub fn process_transaction(&mut self, ixs: &[Instruction]) -> ProgramResult {
+        let mut accounts = self.accounts.clone();
+        for (index, ix) in ixs.iter().enumerate() {
+            if ix.program_id.ne(&ore_relayer_api::id()) {
+                continue;
+            }
+            set_instructions_sysvar(&mut accounts, ixs, index);
+            process_instruction(&mut accounts, ix)?;
+        }
+        self.accounts = accounts;
+        Ok(())
+    }
+
+    pub fn process(&mut self, ix: &Instruction) -> ProgramResult {
+        self.process_transaction(&[ix.clone()])
+    }
+
+    /// Processes a relay program instruction invoked by another program, from the only
+    /// instruction of a transaction. Changes are discarded on error.
+    pub fn process_cpi(&mut self, outer: &Instruction, ix: &Instruction) -> ProgramResult {
+        let mut accounts = self.accounts.clone();
+        set_instructions_sysvar(&mut accounts, &[outer.clone()], 0);
+        STACK_HEIGHT.with(|height| *height.borrow_mut() = TRANSACTION_LEVEL_STACK_HEIGHT + 1);
+        let result = process_instruction(&mut accounts, ix);
+        STACK_HEIGHT.with(|height| *height.borrow_mut() = TRANSACTION_LEVEL_STACK_HEIGHT);
+        result?;
+        self.accounts = accounts;
+        Ok(())
+    }
+}
+
+/// Serves the instructions of a transaction through the instructions sysvar, as seen by the
+/// instruction at the given index.
+fn set_instructions_sysvar(
+    accounts: &mut HashMap<Pubkey, Account>,
+    ixs: &[Instruction],
+    index: usize,
+) {
+    let mut instructions_data = instructions_sysvar_data(ixs);
+    let len = instructions_data.len();
+    instructions_data[len - 2..].copy_from_slice(&(index as u16).to_le_bytes());
+    accounts.insert(
+        sysvar::instructions::id(),
+        Account {
+            owner: sysvar::id(),
+            lamports: 1,
+            data: instructions_data,
+            executable: false,
+        },
+    );
+}
+
+fn instructions_sysvar_data(ixs: &[Instruction]) -> Vec<u8> {
+    let borrowed: Vec<BorrowedInstruction> = ixs
+        .iter()
+        .map(|ix| BorrowedInstruction {
+            program_id: &ix.program_id,
+            accounts: ix
+                .accounts
+                .iter()
+                .map(|meta| BorrowedAccountMeta {
+                    pubkey: &meta.pubkey,
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+                .collect(),
+            data: &ix.data,
+        })
+        .collect();
+    construct_instructions_data(&borrowed)
+}
+
+/// Serializes the accounts of an instruction in the layout the runtime passes to the entrypoint,
+/// runs the program, and writes the accounts back.
+fn process_instruction(accounts: &mut HashMap<Pubkey, Account>, ix: &Instruction) -> ProgramResult {
+    let mut bytes = vec![];
+    bytes.extend((ix.accounts.len() as u64).to_le_bytes());
+    for (i, meta) in ix.accounts.iter().enumerate() {
+        if let Some(dup) = ix.accounts[..i]
+            .iter()
+            .position(|m| m.pubkey.eq(&meta.pubkey))
+        {
+            bytes.extend([dup as u8; 8]);
+            continue;
+        }
+        let account = accounts.get(&meta.pubkey).cloned().unwrap_or(Account {
+            owner: system_program::id(),
+            ..Account::default()
+        });
+        bytes.extend([
+            NON_DUP_MARKER,
+            meta.is_signer as u8,
+            meta.is_writable as u8,
+            account.executable as u8,
+        ]);
+        bytes.extend([0; 4]);
+        bytes.extend(meta.pubkey.as_ref());
+        bytes.extend(account.owner.as_ref());
+        bytes.extend(account.lamports.to_le_bytes());
+        bytes.extend((account.data.len() as u64).to_le_bytes());
+        bytes.extend(&account.data);
+        bytes.resize(bytes.len() + MAX_PERMITTED_DATA_INCREASE, 0);
+        bytes.resize(bytes.len().next_multiple_of(8), 0);
+        bytes.extend(0u64.to_le_bytes());
+    }
+    bytes.extend((ix.data.len() as u64).to_le_bytes());
+    bytes.extend(&ix.data);
+    bytes.extend(ix.program_id.as_ref());
+
+    // Copy into an 8-byte aligned buffer
+    let mut input = vec![0u64; bytes.len().div_ceil(8)];
+    for (word, chunk) in input.iter_mut().zip(bytes.chunks(8)) {
+        let mut buf = [0; 8];
+        buf[..chunk.len()].copy_from_slice(chunk);
+        *word = u64::from_ne_bytes(buf);
+    }
+
+    let (program_id, infos, data) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+    ore_relayer::process_instruction(program_id, &infos, data)?;
+    for info in infos.iter() {
+        accounts.insert(
+            *info.key,
+            Account {
+                owner: *info.owner,
+                lamports: info.lamports(),
+                data: info.data.borrow().to_vec(),
+                executable: info.executable,
+            },
+        );
+    }
+    Ok(())
+}
+
+/// Sets the config account, with the program unpaused.
+pub fn set_config(bank: &mut Bank) {
+    bank.set(
+        CONFIG_ADDRESS,
+        ore_relayer_api::id(),
+        account_data(Config {
+            admin: Pubkey::new_unique(),
+            paused: 0,
+        }),
+    );
+}
+
+/// Returns a bonded relayer mining with the given miner, at a commission of 10%.
+pub fn relayer_fixture(miner: Pubkey) -> Relayer {
+    Relayer {
+        authority: Pubkey::new_unique(),
+        bump: 0,
+        miner,
+        commission: 1_000,
+        beneficiary: Pubkey::new_unique(),
+        admin_paused: 0,
+        self_paused: 0,
+        bond: MIN_RELAYER_BOND,
+        unbonding_slot: 0,
+        accrued_commission: 0,
+    }
+}
+
+/// Returns the vault which holds the commission accrued by a relayer.
+pub fn relayer_vault(relayer: Pubkey) -> Pubkey {
+    spl_associated_token_account::get_associated_token_address(&relayer, &MINT_ADDRESS)
+}
+
+/// Returns an escrow of the given authority, bound to the given relayer at a commission of 10%
+/// with the default fee limits.
+pub fn escrow_fixture(authority: Pubkey, relayer: Pubkey) -> Escrow {
+    let (_, bump) =
+        Pubkey::find_program_address(&[ESCROW, authority.as_ref()], &ore_relayer_api::id());
+    Escrow {
+        authority,
+        bump: bump as u64,
+        version: ESCROW_VERSION,
+        relayer,
+        commission: 1_000,
+        max_fee_per_collect: DEFAULT_MAX_FEE_PER_COLLECT,
+        epoch_fee_budget: DEFAULT_EPOCH_FEE_BUDGET,
+        seed_authority: authority,
+        ..Default::default()
+    }
+}
+
+/// Returns a proof of the given escrow, mined by the given miner with the given balance and last
+/// hash.
+pub fn proof_fixture(escrow: Pubkey, miner: Pubkey, balance: u64, last_hash: [u8; 32]) -> Proof {
+    Proof {
+        authority: escrow,
+        balance,
+        challenge: [0; 32],
+        last_hash,
+        last_hash_at: 0,
+        last_stake_at: 0,
+        miner,
+        total_hashes: 0,
+        total_rewards: 0,
+    }
+}
+
+pub fn account_data<T: Copy + Discriminator + AccountDeserialize>(value: T) -> Vec<u8> {
+    let mut data = vec![0; 8 + size_of::<T>()];
+    data[0] = T::discriminator();
+    *T::try_from_bytes_mut(&mut data).unwrap() = value;
+    data
+}
+
+pub fn mint_data() -> Vec<u8> {
+    let mut data = vec![0; spl_token::state::Mint::LEN];
+    spl_token::state::Mint {
+        mint_authority: COption::Some(TREASURY_ADDRESS),
+        supply: 0,
+        decimals: TOKEN_DECIMALS,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    }
+    .pack_into_slice(&mut data);
+    data
+}
+
+pub fn token_account_data(owner: Pubkey, amount: u64) -> Vec<u8> {
+    let mut data = vec![0; spl_token::state::Account::LEN];
+    spl_token::state::Account {
+        mint: MINT_ADDRESS,
+        owner,
+        amount,
+        delegate: COption::None,
+        state: spl_token::state::AccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None,
+    }
+    .pack_into_slice(&mut data);
+    data
+}
@@ -0,0 +1,115 @@
+mod common;
+
+use common::*;
+use ore_api::consts::{MINT_ADDRESS, PROOF};
+use ore_relayer_api::{consts::*, error::RelayError, instruction::*, state::Escrow};
+use solana_program::pubkey::Pubkey;
+
+/// Sets up a relayer at a commission of 10% and an escrow bound to it, whose proof has mined
+/// 10,000 since it was opened. Returns the relayer, the escrow and the escrow token account.
+fn setup(bank: &mut Bank, relayer_miner: Pubkey, authority: Pubkey) -> (Pubkey, Pubkey, Pubkey) {
+    let (escrow_address, _) =
+        Pubkey::find_program_address(&[ESCROW, authority.as_ref()], &ore_relayer_api::id());
+    let (proof_address, _) =
+        Pubkey::find_program_address(&[PROOF, escrow_address.as_ref()], &ore_api::id());
+    let escrow_tokens =
+        spl_associated_token_account::get_associated_token_address(&escrow_address, &MINT_ADDRESS);
+    set_config(bank);
+    let relayer_address = bank.set_relayer(relayer_fixture(relayer_miner));
+    bank.set(
+        escrow_address,
+        ore_relayer_api::id(),
+        account_data(Escrow {
+            last_hash: [1; 32],
+            ..escrow_fixture(authority, relayer_address)
+        }),
+    );
+    bank.set(
+        escrow_tokens,
+        spl_token::id(),
+        token_account_data(escrow_address, 0),
+    );
+    bank.set(
+        proof_address,
+        ore_api::id(),
+        account_data(proof_fixture(
+            escrow_address,
+            relayer_miner,
+            10_000,
+            [2; 32],
+        )),
+    );
+    (relayer_address, escrow_address, escrow_tokens)
+}
+
+#[test]
+fn compound_requires_opt_in() {
+    let mut bank = Bank::new();
+    let relayer_miner = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let (relayer_address, escrow_address, _) = setup(&mut bank, relayer_miner, authority);
+    take_invoked();
+
+    // Escrows compound only once the user opts in
+    assert_eq!(
+        bank.process(&compound(relayer_miner, relayer_address, authority, 0)),
+        Err(RelayError::CompoundDisabled.into())
+    );
+    assert!(take_invoked().is_empty());
+
+    // Opting out again disables it
+    assert_eq!(
+        bank.process(&configure_compound(authority, authority, true)),
+        Ok(())
+    );
+    assert_eq!(bank.state::<Escrow>(&escrow_address).compound, 1);
+    assert_eq!(
+        bank.process(&configure_compound(authority, authority, false)),
+        Ok(())
+    );
+    assert_eq!(bank.state::<Escrow>(&escrow_address).compound, 0);
+    assert_eq!(
+        bank.process(&compound(relayer_miner, relayer_address, authority, 0)),
+        Err(RelayError::CompoundDisabled.into())
+    );
+}
+
+#[test]
+fn compound_restakes_net_reward() {
+    let mut bank = Bank::new();
+    let relayer_miner = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let (relayer_address, escrow_address, escrow_tokens) =
+        setup(&mut bank, relayer_miner, authority);
+    assert_eq!(
+        bank.process(&configure_compound(authority, authority, true)),
+        Ok(())
+    );
+    take_invoked();
+
+    // Commission is claimed into the relayer vault, and the net reward is claimed to the escrow
+    // and staked back
+    assert_eq!(
+        bank.process(&compound(relayer_miner, relayer_address, authority, 0)),
+        Ok(())
+    );
+    assert_eq!(
+        take_invoked(),
+        vec![
+            ore_api::instruction::claim(escrow_address, relayer_vault(relayer_address), 1_000),
+            ore_api::instruction::claim(escrow_address, escrow_tokens, 9_000),
+            ore_api::instruction::stake(escrow_address, escrow_tokens, 9_000),
+        ]
+    );
+    assert_eq!(bank.token_amount(&escrow_tokens), 0);
+    assert_eq!(bank.token_amount(&relayer_vault(relayer_address)), 1_000);
+    let escrow = bank.state::<Escrow>(&escrow_address);
+    assert_eq!(escrow.last_balance, 9_000);
+    assert_eq!(escrow.last_hash, [2; 32]);
+
+    // Commission is collected once per hash
+    assert_eq!(
+        bank.process(&compound(relayer_miner, relayer_address, authority, 0)),
+        Err(RelayError::DuplicateCollect.into())
+    );
+}
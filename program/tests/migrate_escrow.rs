@@ -0,0 +1,115 @@
+mod common;
+
+use std::mem::size_of;
+
+use common::*;
+use ore_api::consts::PROOF;
+use ore_relayer_api::{
+    consts::*,
+    error::RelayError,
+    instruction::*,
+    state::{Escrow, EscrowV1, Relayer},
+};
+use ore_utils::{AccountDeserialize, Discriminator};
+use solana_program::pubkey::Pubkey;
+
+/// Sets up a legacy escrow mined by the given miner, and a relayer mining with the relayer miner.
+fn setup(bank: &mut Bank, authority: Pubkey, miner: Pubkey, relayer_miner: Pubkey) -> Pubkey {
+    let (escrow_address, escrow_bump) =
+        Pubkey::find_program_address(&[ESCROW, authority.as_ref()], &ore_relayer_api::id());
+    let (proof_address, _) =
+        Pubkey::find_program_address(&[PROOF, escrow_address.as_ref()], &ore_api::id());
+    set_config(bank);
+    let relayer_address = bank.set_relayer(Relayer {
+        commission: 500,
+        ..relayer_fixture(relayer_miner)
+    });
+
+    // Legacy escrows recorded the proof balance before commission was claimed
+    let mut escrow_data = vec![0; 8 + size_of::<EscrowV1>()];
+    escrow_data[0] = EscrowV1::discriminator();
+    *EscrowV1::try_from_bytes_mut(&mut escrow_data).unwrap() = EscrowV1 {
+        authority,
+        bump: escrow_bump as u64,
+        last_hash: [1; 32],
+        last_balance: 1_010,
+    };
+    bank.set(escrow_address, ore_relayer_api::id(), escrow_data);
+    bank.set_lamports(authority, 1_000_000_000);
+    bank.set(
+        proof_address,
+        ore_api::id(),
+        account_data(proof_fixture(escrow_address, miner, 1_000, [1; 32])),
+    );
+    relayer_address
+}
+
+#[test]
+fn migrate_legacy_escrow() {
+    let mut bank = Bank::new();
+    let authority = Pubkey::new_unique();
+    let miner = Pubkey::new_unique();
+    let relayer_address = setup(&mut bank, authority, miner, miner);
+    let (escrow_address, _) =
+        Pubkey::find_program_address(&[ESCROW, authority.as_ref()], &ore_relayer_api::id());
+
+    // Legacy escrows hold no version
+    let escrow_data = bank.get(&escrow_address).data;
+    assert_eq!(Escrow::version_of(&escrow_data), Ok(ESCROW_LEGACY_VERSION));
+
+    // Migrate to the current version
+    assert_eq!(
+        bank.process(&migrate_escrow(authority, relayer_address, 500)),
+        Ok(())
+    );
+    let escrow_data = bank.get(&escrow_address).data;
+    assert_eq!(escrow_data.len(), 8 + size_of::<Escrow>());
+    assert_eq!(Escrow::version_of(&escrow_data), Ok(ESCROW_VERSION));
+    let escrow = bank.state::<Escrow>(&escrow_address);
+    assert_eq!(escrow.authority, authority);
+    assert_eq!(escrow.seed_authority, authority);
+    assert_eq!(escrow.last_hash, [1; 32]);
+    assert_eq!(escrow.last_balance, 1_000);
+    assert_eq!(escrow.relayer, relayer_address);
+    assert_eq!(escrow.commission, 500);
+    assert_eq!(escrow.max_fee_per_collect, DEFAULT_MAX_FEE_PER_COLLECT);
+    assert_eq!(escrow.epoch_fee_budget, DEFAULT_EPOCH_FEE_BUDGET);
+
+    // Escrows on the current version cannot be migrated again
+    assert_eq!(
+        bank.process(&migrate_escrow(authority, relayer_address, 500)),
+        Err(RelayError::InvalidEscrowVersion.into())
+    );
+}
+
+#[test]
+fn migrate_legacy_escrow_to_wrong_relayer() {
+    let mut bank = Bank::new();
+    let authority = Pubkey::new_unique();
+    let relayer_address = setup(
+        &mut bank,
+        authority,
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+
+    // The relayer must be the one mining the proof
+    assert_eq!(
+        bank.process(&migrate_escrow(authority, relayer_address, 500)),
+        Err(RelayError::InvalidMiner.into())
+    );
+}
+
+#[test]
+fn migrate_legacy_escrow_at_unexpected_commission() {
+    let mut bank = Bank::new();
+    let authority = Pubkey::new_unique();
+    let miner = Pubkey::new_unique();
+    let relayer_address = setup(&mut bank, authority, miner, miner);
+
+    // The relayer commission must be the one the user expects
+    assert_eq!(
+        bank.process(&migrate_escrow(authority, relayer_address, 400)),
+        Err(RelayError::InvalidCommission.into())
+    );
+}
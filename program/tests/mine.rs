@@ -0,0 +1,113 @@
+mod common;
+
+use common::*;
+use drillx::Solution;
+use ore_api::{
+    consts::{BUS_ADDRESSES, CONFIG_ADDRESS as ORE_CONFIG_ADDRESS, PROOF},
+    state::{Bus, Config as OreConfig, Proof},
+};
+use ore_relayer_api::{
+    consts::*,
+    error::RelayError,
+    instruction::*,
+    state::{Escrow, Relayer},
+};
+use solana_program::{pubkey::Pubkey, sysvar};
+
+#[test]
+fn mine_and_collect() {
+    let mut bank = Bank::new();
+    let relayer_authority = Pubkey::new_unique();
+    let relayer_miner = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let (escrow_address, _) =
+        Pubkey::find_program_address(&[ESCROW, authority.as_ref()], &ore_relayer_api::id());
+    let (proof_address, _) =
+        Pubkey::find_program_address(&[PROOF, escrow_address.as_ref()], &ore_api::id());
+    set_config(&mut bank);
+    bank.set(
+        ORE_CONFIG_ADDRESS,
+        ore_api::id(),
+        account_data(OreConfig {
+            base_reward_rate: 0,
+            last_reset_at: 0,
+            min_difficulty: 0,
+            top_balance: 0,
+        }),
+    );
+    bank.set(
+        BUS_ADDRESSES[0],
+        ore_api::id(),
+        account_data(Bus {
+            id: 0,
+            rewards: 0,
+            theoretical_rewards: 0,
+            top_balance: 0,
+        }),
+    );
+    bank.set(sysvar::slot_hashes::id(), sysvar::id(), vec![]);
+    let relayer_address = bank.set_relayer(Relayer {
+        authority: relayer_authority,
+        ..relayer_fixture(relayer_miner)
+    });
+    bank.set(
+        escrow_address,
+        ore_relayer_api::id(),
+        account_data(Escrow {
+            last_hash: [1; 32],
+            ..escrow_fixture(authority, relayer_address)
+        }),
+    );
+    bank.set(
+        proof_address,
+        ore_api::id(),
+        account_data(proof_fixture(escrow_address, relayer_miner, 0, [1; 32])),
+    );
+    let solution = Solution::new([3; 16], [4; 8]);
+
+    // Only the relayer miner may mine
+    assert_eq!(
+        bank.process(&mine(
+            Pubkey::new_unique(),
+            relayer_address,
+            authority,
+            BUS_ADDRESSES[0],
+            solution,
+            0
+        )),
+        Err(RelayError::UnauthorizedRelayer.into())
+    );
+
+    // Mine submits the solution and accrues commission on the reward
+    set_mine_result(1_000, [2; 32]);
+    set_slot(100);
+    assert_eq!(
+        bank.process(&mine(
+            relayer_miner,
+            relayer_address,
+            authority,
+            BUS_ADDRESSES[0],
+            solution,
+            0
+        )),
+        Ok(())
+    );
+    assert_eq!(
+        take_invoked()[0],
+        ore_api::instruction::mine(relayer_miner, escrow_address, BUS_ADDRESSES[0], solution)
+    );
+    let escrow = bank.state::<Escrow>(&escrow_address);
+    assert_eq!(escrow.last_hash, [2; 32]);
+    assert_eq!(escrow.last_balance, 900);
+    assert_eq!(bank.state::<Proof>(&proof_address).balance, 900);
+    assert_eq!(bank.token_amount(&relayer_vault(relayer_address)), 100);
+    assert_eq!(escrow.stats.collects, 1);
+    let relayer = bank.state::<Relayer>(&relayer_address);
+    assert_eq!(relayer.accrued_commission, 100);
+
+    // The reward of a mine cannot be collected again
+    assert_eq!(
+        bank.process(&collect(relayer_miner, relayer_address, authority, 0)),
+        Err(RelayError::DuplicateCollect.into())
+    );
+}
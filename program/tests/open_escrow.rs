@@ -0,0 +1,180 @@
+mod common;
+
+use common::*;
+use ore_api::{consts::PROOF, state::Proof};
+use ore_relayer_api::{consts::*, error::RelayError, instruction::*, state::Escrow};
+use solana_program::pubkey::Pubkey;
+
+/// Sets up an active config, a funded payer and a relayer mining with the given miner.
+/// Returns the relayer.
+fn setup(bank: &mut Bank, payer: Pubkey, relayer_miner: Pubkey) -> Pubkey {
+    set_config(bank);
+    bank.set_lamports(payer, 1_000_000_000);
+    set_relayer(bank, relayer_miner)
+}
+
+/// Sets up a relayer mining with the given miner, at a commission of 10%.
+fn set_relayer(bank: &mut Bank, relayer_miner: Pubkey) -> Pubkey {
+    bank.set_relayer(relayer_fixture(relayer_miner))
+}
+
+#[test]
+fn open_escrow_binds_relayer() {
+    let mut bank = Bank::new();
+    let authority = Pubkey::new_unique();
+    let payer = Pubkey::new_unique();
+    let relayer_miner = Pubkey::new_unique();
+    let relayer_address = setup(&mut bank, payer, relayer_miner);
+    let (escrow_address, escrow_bump) =
+        Pubkey::find_program_address(&[ESCROW, authority.as_ref()], &ore_relayer_api::id());
+    let (proof_address, _) =
+        Pubkey::find_program_address(&[PROOF, escrow_address.as_ref()], &ore_api::id());
+
+    // Open an escrow bound to the relayer
+    assert_eq!(
+        bank.process(&open_escrow(
+            authority,
+            payer,
+            relayer_address,
+            relayer_miner,
+            1_000,
+            0
+        )),
+        Ok(())
+    );
+    let escrow = bank.state::<Escrow>(&escrow_address);
+    assert_eq!(escrow.authority, authority);
+    assert_eq!(escrow.bump, escrow_bump as u64);
+    assert_eq!(escrow.version, ESCROW_VERSION);
+    assert_eq!(escrow.relayer, relayer_address);
+    assert_eq!(escrow.commission, 1_000);
+    assert_eq!(escrow.seed_authority, authority);
+    assert_eq!(escrow.max_fee_per_collect, DEFAULT_MAX_FEE_PER_COLLECT);
+    assert_eq!(escrow.epoch_fee_budget, DEFAULT_EPOCH_FEE_BUDGET);
+    let proof = bank.state::<Proof>(&proof_address);
+    assert_eq!(proof.authority, escrow_address);
+    assert_eq!(proof.miner, relayer_miner);
+
+    // Another relayer sharing the miner may not collect
+    let other_relayer = set_relayer(&mut bank, relayer_miner);
+    let mut proof = proof;
+    proof.balance = 1_000;
+    proof.last_hash = [1; 32];
+    bank.set(proof_address, ore_api::id(), account_data(proof));
+    assert_eq!(
+        bank.process(&collect(relayer_miner, other_relayer, authority, 0)),
+        Err(RelayError::UnauthorizedRelayer.into())
+    );
+
+    // The selected relayer may
+    assert_eq!(
+        bank.process(&collect(relayer_miner, relayer_address, authority, 0)),
+        Ok(())
+    );
+}
+
+#[test]
+fn open_escrow_validates_relayer() {
+    let mut bank = Bank::new();
+    let authority = Pubkey::new_unique();
+    let payer = Pubkey::new_unique();
+    let relayer_miner = Pubkey::new_unique();
+    let relayer_address = setup(&mut bank, payer, relayer_miner);
+    let (escrow_address, _) =
+        Pubkey::find_program_address(&[ESCROW, authority.as_ref()], &ore_relayer_api::id());
+
+    // The relayer account must be the one selected in the args
+    let other_relayer = set_relayer(&mut bank, relayer_miner);
+    let mut ix = open_escrow(authority, payer, relayer_address, relayer_miner, 1_000, 0);
+    ix.accounts[3].pubkey = other_relayer;
+    assert_eq!(
+        bank.process(&ix),
+        Err(RelayError::UnauthorizedRelayer.into())
+    );
+
+    // The miner must be the relayer miner
+    assert_eq!(
+        bank.process(&open_escrow(
+            authority,
+            payer,
+            relayer_address,
+            Pubkey::new_unique(),
+            1_000,
+            0
+        )),
+        Err(RelayError::InvalidMiner.into())
+    );
+    assert!(bank.get(&escrow_address).data.is_empty());
+}
+
+#[test]
+fn open_escrow_requires_agreed_commission() {
+    let mut bank = Bank::new();
+    let authority = Pubkey::new_unique();
+    let payer = Pubkey::new_unique();
+    let relayer_miner = Pubkey::new_unique();
+    let relayer_address = setup(&mut bank, payer, relayer_miner);
+
+    // The commission agreed to by the user must be the relayer's current rate
+    assert_eq!(
+        bank.process(&open_escrow(
+            authority,
+            payer,
+            relayer_address,
+            relayer_miner,
+            500,
+            0
+        )),
+        Err(RelayError::InvalidCommission.into())
+    );
+}
+
+#[test]
+fn open_escrow_records_sponsored_rent() {
+    let mut bank = Bank::new();
+    let authority = Pubkey::new_unique();
+    let payer = Pubkey::new_unique();
+    let relayer_miner = Pubkey::new_unique();
+    let relayer_address = setup(&mut bank, payer, relayer_miner);
+    let (escrow_address, _) =
+        Pubkey::find_program_address(&[ESCROW, authority.as_ref()], &ore_relayer_api::id());
+
+    // The rent debt is capped by the rent the payer funded
+    assert_eq!(
+        bank.process(&open_escrow(
+            authority,
+            payer,
+            relayer_address,
+            relayer_miner,
+            1_000,
+            u64::MAX
+        )),
+        Ok(())
+    );
+    let rent_paid = 1_000_000_000 - bank.get(&payer).lamports;
+    assert!(rent_paid.gt(&0));
+    assert_eq!(bank.get(&authority).lamports, 0);
+    let escrow = bank.state::<Escrow>(&escrow_address);
+    assert_eq!(escrow.rent_debt, rent_paid);
+    assert_eq!(escrow.rent_payer, payer);
+
+    // Users funding their own escrow owe no rent debt
+    let other_authority = Pubkey::new_unique();
+    bank.set_lamports(other_authority, 1_000_000_000);
+    let (other_escrow_address, _) =
+        Pubkey::find_program_address(&[ESCROW, other_authority.as_ref()], &ore_relayer_api::id());
+    assert_eq!(
+        bank.process(&open_escrow(
+            other_authority,
+            other_authority,
+            relayer_address,
+            relayer_miner,
+            1_000,
+            u64::MAX
+        )),
+        Ok(())
+    );
+    let escrow = bank.state::<Escrow>(&other_escrow_address);
+    assert_eq!(escrow.rent_debt, 0);
+    assert_eq!(escrow.rent_payer, Pubkey::default());
+}
@@ -0,0 +1,173 @@
+mod common;
+
+use common::*;
+use ore_api::consts::PROOF;
+use ore_relayer_api::{
+    consts::*,
+    error::RelayError,
+    instruction::*,
+    state::{Config, Escrow, Relayer},
+};
+use solana_program::pubkey::Pubkey;
+
+/// Sets up a config with the given admin, a relayer registered by the relayer authority, and an
+/// escrow bound to it which has collected 1,000. Returns the relayer and the relayer miner.
+fn setup(
+    bank: &mut Bank,
+    admin: Pubkey,
+    relayer_authority: Pubkey,
+    authority: Pubkey,
+) -> (Pubkey, Pubkey) {
+    let relayer_miner = Pubkey::new_unique();
+    let (escrow_address, _) =
+        Pubkey::find_program_address(&[ESCROW, authority.as_ref()], &ore_relayer_api::id());
+    let (proof_address, _) =
+        Pubkey::find_program_address(&[PROOF, escrow_address.as_ref()], &ore_api::id());
+    bank.set(
+        CONFIG_ADDRESS,
+        ore_relayer_api::id(),
+        account_data(Config { admin, paused: 0 }),
+    );
+    let relayer_address = bank.set_relayer(Relayer {
+        authority: relayer_authority,
+        ..relayer_fixture(relayer_miner)
+    });
+    bank.set(
+        escrow_address,
+        ore_relayer_api::id(),
+        account_data(Escrow {
+            last_hash: [1; 32],
+            last_balance: 1_000,
+            ..escrow_fixture(authority, relayer_address)
+        }),
+    );
+    bank.set(
+        proof_address,
+        ore_api::id(),
+        account_data(proof_fixture(escrow_address, relayer_miner, 2_000, [2; 32])),
+    );
+    (relayer_address, relayer_miner)
+}
+
+#[test]
+fn global_pause_halts_fund_movements() {
+    let mut bank = Bank::new();
+    let admin = Pubkey::new_unique();
+    let relayer_authority = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let (relayer_address, relayer_miner) = setup(&mut bank, admin, relayer_authority, authority);
+    let beneficiary = Pubkey::new_unique();
+    bank.set(
+        beneficiary,
+        spl_token::id(),
+        token_account_data(authority, 0),
+    );
+
+    // Only the admin may pause the program
+    assert_eq!(
+        bank.process(&set_paused(relayer_authority, true)),
+        Err(RelayError::UnauthorizedAdmin.into())
+    );
+    assert_eq!(bank.process(&set_paused(admin, true)), Ok(()));
+    assert_eq!(bank.state::<Config>(&CONFIG_ADDRESS).paused, 1);
+
+    // Relayer-side instructions are halted
+    let relayer_beneficiary = bank.state::<Relayer>(&relayer_address).beneficiary;
+    assert_eq!(
+        bank.process(&collect(relayer_miner, relayer_address, authority, 0)),
+        Err(RelayError::ProgramPaused.into())
+    );
+    assert_eq!(
+        bank.process(&deregister_relayer(relayer_authority, relayer_beneficiary)),
+        Err(RelayError::ProgramPaused.into())
+    );
+
+    // So are instructions which settle commission or move the bond
+    assert_eq!(
+        bank.process(&slash_relayer(authority, authority, relayer_address)),
+        Err(RelayError::ProgramPaused.into())
+    );
+    assert_eq!(
+        bank.process(&revoke_relayer(
+            authority,
+            authority,
+            relayer_address,
+            Pubkey::new_unique()
+        )),
+        Err(RelayError::ProgramPaused.into())
+    );
+
+    // Users may still claim their collected balance
+    assert_eq!(
+        bank.process(&claim(authority, authority, beneficiary, 1_000)),
+        Ok(())
+    );
+
+    // Unpausing resumes relayer-side instructions
+    assert_eq!(bank.process(&set_paused(admin, false)), Ok(()));
+    assert_eq!(
+        bank.process(&collect(relayer_miner, relayer_address, authority, 0)),
+        Ok(())
+    );
+}
+
+#[test]
+fn relayer_pause_halts_relayer() {
+    let mut bank = Bank::new();
+    let admin = Pubkey::new_unique();
+    let relayer_authority = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let (relayer_address, relayer_miner) = setup(&mut bank, admin, relayer_authority, authority);
+
+    // An admin stop halts collects and withholds the bond
+    let relayer_beneficiary = bank.state::<Relayer>(&relayer_address).beneficiary;
+    assert_eq!(
+        bank.process(&set_relayer_paused(admin, relayer_address, true)),
+        Ok(())
+    );
+    assert_eq!(
+        bank.process(&collect(relayer_miner, relayer_address, authority, 0)),
+        Err(RelayError::RelayerPaused.into())
+    );
+    assert_eq!(
+        bank.process(&deregister_relayer(relayer_authority, relayer_beneficiary)),
+        Err(RelayError::RelayerPaused.into())
+    );
+
+    // The relayer may not clear an admin stop, only set its own
+    assert_eq!(
+        bank.process(&set_relayer_paused(
+            relayer_authority,
+            relayer_address,
+            false
+        )),
+        Ok(())
+    );
+    assert_eq!(bank.state::<Relayer>(&relayer_address).admin_paused, 1);
+    assert_eq!(
+        bank.process(&set_relayer_paused(admin, relayer_address, false)),
+        Ok(())
+    );
+
+    // A self stop halts collects, but the relayer may still deregister
+    assert_eq!(
+        bank.process(&set_relayer_paused(
+            relayer_authority,
+            relayer_address,
+            true
+        )),
+        Ok(())
+    );
+    assert_eq!(
+        bank.process(&collect(relayer_miner, relayer_address, authority, 0)),
+        Err(RelayError::RelayerPaused.into())
+    );
+    assert_eq!(
+        bank.process(&deregister_relayer(relayer_authority, relayer_beneficiary)),
+        Ok(())
+    );
+    assert!(bank
+        .state::<Relayer>(&relayer_address)
+        .unbonding_slot
+        .gt(&0));
+}
@@ -0,0 +1,197 @@
+mod common;
+
+use common::*;
+use ore_api::{consts::PROOF, state::Proof};
+use ore_relayer_api::{
+    consts::*,
+    error::RelayError,
+    instruction::*,
+    state::{Escrow, Relayer},
+};
+use solana_program::pubkey::Pubkey;
+
+/// Sets up a relayer and an escrow bound to it which has collected 5,000, and registers a
+/// payout destination owned by the authority. Returns the relayer, the escrow, the proof and
+/// the destination.
+fn setup(
+    bank: &mut Bank,
+    relayer_miner: Pubkey,
+    authority: Pubkey,
+) -> (Pubkey, Pubkey, Pubkey, Pubkey) {
+    let relayer_address = Pubkey::new_unique();
+    let destination = Pubkey::new_unique();
+    let (escrow_address, _) =
+        Pubkey::find_program_address(&[ESCROW, authority.as_ref()], &ore_relayer_api::id());
+    let (proof_address, _) =
+        Pubkey::find_program_address(&[PROOF, escrow_address.as_ref()], &ore_api::id());
+    set_config(bank);
+    bank.set(
+        relayer_address,
+        ore_relayer_api::id(),
+        account_data(relayer_fixture(relayer_miner)),
+    );
+    bank.set(
+        escrow_address,
+        ore_relayer_api::id(),
+        account_data(Escrow {
+            last_hash: [2; 32],
+            last_balance: 5_000,
+            ..escrow_fixture(authority, relayer_address)
+        }),
+    );
+    bank.set(
+        proof_address,
+        ore_api::id(),
+        account_data(proof_fixture(escrow_address, relayer_miner, 5_000, [2; 32])),
+    );
+    bank.set(
+        destination,
+        spl_token::id(),
+        token_account_data(authority, 0),
+    );
+    (relayer_address, escrow_address, proof_address, destination)
+}
+
+#[test]
+fn payout_pays_collected_balance_to_destination() {
+    let mut bank = Bank::new();
+    let relayer_miner = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let (relayer_address, escrow_address, proof_address, destination) =
+        setup(&mut bank, relayer_miner, authority);
+
+    // Payouts are disabled until the user registers a destination
+    assert_eq!(
+        bank.process(&payout(
+            relayer_miner,
+            relayer_address,
+            authority,
+            destination,
+            0
+        )),
+        Err(RelayError::InvalidBeneficiary.into())
+    );
+    assert_eq!(
+        bank.process(&configure_payout(authority, authority, destination, 6_000)),
+        Ok(())
+    );
+
+    // The collected balance must reach the threshold
+    assert_eq!(
+        bank.process(&payout(
+            relayer_miner,
+            relayer_address,
+            authority,
+            destination,
+            0
+        )),
+        Err(RelayError::PayoutThresholdNotMet.into())
+    );
+    assert_eq!(
+        bank.process(&configure_payout(authority, authority, destination, 5_000)),
+        Ok(())
+    );
+
+    // Only the registered destination may be paid
+    let other_destination = Pubkey::new_unique();
+    bank.set(
+        other_destination,
+        spl_token::id(),
+        token_account_data(authority, 0),
+    );
+    assert_eq!(
+        bank.process(&payout(
+            relayer_miner,
+            relayer_address,
+            authority,
+            other_destination,
+            0
+        )),
+        Err(RelayError::InvalidBeneficiary.into())
+    );
+
+    // Commission must be collected on the latest hash first
+    let mut proof = bank.state::<Proof>(&proof_address);
+    proof.last_hash = [3; 32];
+    bank.set(proof_address, ore_api::id(), account_data(proof));
+    assert_eq!(
+        bank.process(&payout(
+            relayer_miner,
+            relayer_address,
+            authority,
+            destination,
+            0
+        )),
+        Err(RelayError::UncollectedCommission.into())
+    );
+    proof.last_hash = [2; 32];
+    bank.set(proof_address, ore_api::id(), account_data(proof));
+
+    // Then the collected balance is claimed to the destination
+    take_invoked();
+    assert_eq!(
+        bank.process(&payout(
+            relayer_miner,
+            relayer_address,
+            authority,
+            destination,
+            0
+        )),
+        Ok(())
+    );
+    assert_eq!(
+        take_invoked(),
+        vec![ore_api::instruction::claim(
+            escrow_address,
+            destination,
+            5_000
+        )]
+    );
+    assert_eq!(bank.token_amount(&destination), 5_000);
+    let escrow = bank.state::<Escrow>(&escrow_address);
+    assert_eq!(escrow.last_balance, 0);
+    assert_eq!(escrow.stats.total_claimed, 5_000);
+}
+
+#[test]
+fn payout_requires_bonded_relayer() {
+    let mut bank = Bank::new();
+    let relayer_miner = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let (relayer_address, _, _, destination) = setup(&mut bank, relayer_miner, authority);
+    assert_eq!(
+        bank.process(&configure_payout(authority, authority, destination, 1)),
+        Ok(())
+    );
+
+    // Only the relayer miner may pay out
+    assert_eq!(
+        bank.process(&payout(
+            Pubkey::new_unique(),
+            relayer_address,
+            authority,
+            destination,
+            0
+        )),
+        Err(RelayError::UnauthorizedRelayer.into())
+    );
+
+    // Relayers which are unbonding may not
+    let mut relayer = bank.state::<Relayer>(&relayer_address);
+    relayer.unbonding_slot = 1;
+    bank.set(
+        relayer_address,
+        ore_relayer_api::id(),
+        account_data(relayer),
+    );
+    assert_eq!(
+        bank.process(&payout(
+            relayer_miner,
+            relayer_address,
+            authority,
+            destination,
+            0
+        )),
+        Err(RelayError::RelayerPaused.into())
+    );
+}
@@ -0,0 +1,197 @@
+mod common;
+
+use common::*;
+use ore_relayer_api::{consts::*, error::RelayError, instruction::*, state::Relayer};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+
+/// Sets up an active config, a funded relayer authority and a beneficiary token account.
+/// Returns the relayer address and the beneficiary.
+fn setup(bank: &mut Bank, authority: Pubkey) -> (Pubkey, Pubkey) {
+    let beneficiary = Pubkey::new_unique();
+    let (relayer_address, _) =
+        Pubkey::find_program_address(&[RELAYER, authority.as_ref()], &ore_relayer_api::id());
+    set_config(bank);
+    bank.set(
+        beneficiary,
+        spl_token::id(),
+        token_account_data(authority, 0),
+    );
+    bank.set_lamports(authority, 10 * MIN_RELAYER_BOND);
+    (relayer_address, beneficiary)
+}
+
+#[test]
+fn register_relayer_requires_miner_signature() {
+    let mut bank = Bank::new();
+    let authority = Pubkey::new_unique();
+    let miner = Pubkey::new_unique();
+    let (relayer_address, beneficiary) = setup(&mut bank, authority);
+
+    // The miner must sign
+    let mut ix = register_relayer(authority, miner, beneficiary, 1_000, MIN_RELAYER_BOND);
+    ix.accounts[2].is_signer = false;
+    assert_eq!(
+        bank.process(&ix),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+    assert!(bank.get(&relayer_address).data.is_empty());
+}
+
+#[test]
+fn register_relayer_validates_args() {
+    let mut bank = Bank::new();
+    let authority = Pubkey::new_unique();
+    let miner = Pubkey::new_unique();
+    let (_, beneficiary) = setup(&mut bank, authority);
+
+    // Commission above 100% is rejected
+    assert_eq!(
+        bank.process(&register_relayer(
+            authority,
+            miner,
+            beneficiary,
+            DENOMINATOR_BPS + 1,
+            MIN_RELAYER_BOND
+        )),
+        Err(RelayError::InvalidCommission.into())
+    );
+
+    // Bonds below the minimum are rejected
+    assert_eq!(
+        bank.process(&register_relayer(
+            authority,
+            miner,
+            beneficiary,
+            1_000,
+            MIN_RELAYER_BOND - 1
+        )),
+        Err(RelayError::InsufficientBond.into())
+    );
+}
+
+#[test]
+fn register_update_deregister_relayer() {
+    let mut bank = Bank::new();
+    let authority = Pubkey::new_unique();
+    let miner = Pubkey::new_unique();
+    let (relayer_address, beneficiary) = setup(&mut bank, authority);
+
+    // Register posts the bond
+    set_slot(10);
+    assert_eq!(
+        bank.process(&register_relayer(
+            authority,
+            miner,
+            beneficiary,
+            1_000,
+            MIN_RELAYER_BOND
+        )),
+        Ok(())
+    );
+    let relayer = bank.state::<Relayer>(&relayer_address);
+    assert_eq!(relayer.authority, authority);
+    assert_eq!(relayer.miner, miner);
+    assert_eq!(relayer.beneficiary, beneficiary);
+    assert_eq!(relayer.commission, 1_000);
+    assert_eq!(relayer.bond, MIN_RELAYER_BOND);
+    let relayer_lamports = bank.get(&relayer_address).lamports;
+    assert!(relayer_lamports.gt(&MIN_RELAYER_BOND));
+    let vault_lamports = bank.get(&relayer_vault(relayer_address)).lamports;
+    assert_eq!(bank.token_amount(&relayer_vault(relayer_address)), 0);
+    assert_eq!(
+        bank.get(&authority).lamports,
+        10 * MIN_RELAYER_BOND - relayer_lamports - vault_lamports
+    );
+
+    // Update the commission and beneficiary
+    let new_beneficiary = Pubkey::new_unique();
+    bank.set(
+        new_beneficiary,
+        spl_token::id(),
+        token_account_data(authority, 0),
+    );
+    assert_eq!(
+        bank.process(&update_relayer(
+            authority,
+            new_beneficiary,
+            DENOMINATOR_BPS + 1
+        )),
+        Err(RelayError::InvalidCommission.into())
+    );
+    assert_eq!(
+        bank.process(&update_relayer(authority, new_beneficiary, 500)),
+        Ok(())
+    );
+    let relayer = bank.state::<Relayer>(&relayer_address);
+    assert_eq!(relayer.commission, 500);
+    assert_eq!(relayer.beneficiary, new_beneficiary);
+    assert_eq!(relayer.miner, miner);
+
+    // Only the authority can update the relayer
+    let other = Pubkey::new_unique();
+    assert!(bank
+        .process(&update_relayer(other, new_beneficiary, 0))
+        .is_err());
+
+    // The first deregister request starts the bond lockup
+    set_slot(100);
+    assert_eq!(
+        bank.process(&deregister_relayer(authority, new_beneficiary)),
+        Ok(())
+    );
+    assert_eq!(
+        bank.state::<Relayer>(&relayer_address).unbonding_slot,
+        100 + BOND_LOCKUP_SLOTS
+    );
+
+    // The bond is locked until the lockup ends
+    set_slot(99 + BOND_LOCKUP_SLOTS);
+    assert_eq!(
+        bank.process(&deregister_relayer(authority, new_beneficiary)),
+        Err(RelayError::BondLocked.into())
+    );
+
+    // Commission settled into the vault during the lockup is paid to the beneficiary
+    bank.set(
+        relayer_vault(relayer_address),
+        spl_token::id(),
+        token_account_data(relayer_address, 50),
+    );
+    bank.set_lamports(relayer_vault(relayer_address), vault_lamports);
+
+    // Only the registered beneficiary may receive it
+    set_slot(100 + BOND_LOCKUP_SLOTS);
+    assert_eq!(
+        bank.process(&deregister_relayer(authority, beneficiary)),
+        Err(RelayError::InvalidBeneficiary.into())
+    );
+
+    // Then the relayer account and vault close and return the bond and rent
+    assert_eq!(
+        bank.process(&deregister_relayer(authority, new_beneficiary)),
+        Ok(())
+    );
+    assert_eq!(bank.token_amount(&new_beneficiary), 50);
+    assert!(bank.get(&relayer_vault(relayer_address)).data.is_empty());
+    assert!(bank.get(&relayer_address).data.is_empty());
+    assert_eq!(bank.get(&relayer_address).lamports, 0);
+    assert_eq!(bank.get(&authority).lamports, 10 * MIN_RELAYER_BOND);
+}
+
+#[test]
+fn deregister_relayer_requires_withdrawn_commission() {
+    let mut bank = Bank::new();
+    let authority = Pubkey::new_unique();
+    let miner = Pubkey::new_unique();
+    let (_, beneficiary) = setup(&mut bank, authority);
+    bank.set_relayer(Relayer {
+        authority,
+        beneficiary,
+        accrued_commission: 1,
+        ..relayer_fixture(miner)
+    });
+    assert_eq!(
+        bank.process(&deregister_relayer(authority, beneficiary)),
+        Err(RelayError::UnwithdrawnCommission.into())
+    );
+}
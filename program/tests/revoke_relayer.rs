@@ -0,0 +1,194 @@
+mod common;
+
+use common::*;
+use ore_api::{consts::PROOF, state::Proof};
+use ore_relayer_api::{
+    consts::*,
+    error::RelayError,
+    instruction::*,
+    state::{Escrow, Relayer},
+};
+use solana_program::pubkey::Pubkey;
+
+/// Sets up a relayer registered by the relayer authority. Returns the relayer.
+fn set_relayer(bank: &mut Bank, relayer_authority: Pubkey, relayer_miner: Pubkey) -> Pubkey {
+    set_config(bank);
+    bank.set_relayer(Relayer {
+        authority: relayer_authority,
+        ..relayer_fixture(relayer_miner)
+    })
+}
+
+/// Sets up an escrow bound to the relayer at slot 0, whose proof has mined 1,000. Returns the
+/// escrow and the proof.
+fn set_escrow(
+    bank: &mut Bank,
+    authority: Pubkey,
+    relayer_address: Pubkey,
+    relayer_miner: Pubkey,
+) -> (Pubkey, Pubkey) {
+    let (escrow_address, _) =
+        Pubkey::find_program_address(&[ESCROW, authority.as_ref()], &ore_relayer_api::id());
+    let (proof_address, _) =
+        Pubkey::find_program_address(&[PROOF, escrow_address.as_ref()], &ore_api::id());
+    bank.set(
+        escrow_address,
+        ore_relayer_api::id(),
+        account_data(Escrow {
+            last_hash: [1; 32],
+            ..escrow_fixture(authority, relayer_address)
+        }),
+    );
+    bank.set(
+        proof_address,
+        ore_api::id(),
+        account_data(proof_fixture(escrow_address, relayer_miner, 1_000, [2; 32])),
+    );
+    (escrow_address, proof_address)
+}
+
+/// Credits a reward and a new hash to the proof.
+fn mine_hash(bank: &mut Bank, proof_address: Pubkey, reward: u64, hash: [u8; 32]) {
+    let mut proof = bank.state::<Proof>(&proof_address);
+    proof.balance += reward;
+    proof.last_hash = hash;
+    bank.set(proof_address, ore_api::id(), account_data(proof));
+}
+
+#[test]
+fn revoke_stale_relayer() {
+    let mut bank = Bank::new();
+    let relayer_authority = Pubkey::new_unique();
+    let relayer_miner = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let other_authority = Pubkey::new_unique();
+    let new_miner = Pubkey::new_unique();
+    let relayer_address = set_relayer(&mut bank, relayer_authority, relayer_miner);
+    let (escrow_address, proof_address) =
+        set_escrow(&mut bank, authority, relayer_address, relayer_miner);
+    let (_, other_proof_address) =
+        set_escrow(&mut bank, other_authority, relayer_address, relayer_miner);
+
+    // Collect marks the escrow as live
+    set_slot(100);
+    assert_eq!(
+        bank.process(&collect(relayer_miner, relayer_address, authority, 0)),
+        Ok(())
+    );
+    assert_eq!(
+        bank.state::<Escrow>(&escrow_address)
+            .stats
+            .last_collect_slot,
+        100
+    );
+    assert_eq!(bank.token_amount(&relayer_vault(relayer_address)), 100);
+
+    // The relayer cannot be revoked until the escrow has gone without a collect for the
+    // liveness period
+    set_slot(100 + RELAYER_LIVENESS_SLOTS);
+    assert_eq!(
+        bank.process(&revoke_relayer(
+            authority,
+            authority,
+            relayer_address,
+            new_miner
+        )),
+        Err(RelayError::RelayerLive.into())
+    );
+
+    // Collecting from other escrows does not keep this one live
+    assert_eq!(
+        bank.process(&collect(relayer_miner, relayer_address, other_authority, 0)),
+        Ok(())
+    );
+    mine_hash(&mut bank, other_proof_address, 0, [3; 32]);
+
+    // Rewards mined during the stale period owe no commission, and commission collected before it
+    // stays in the relayer vault
+    mine_hash(&mut bank, proof_address, 1_000, [3; 32]);
+    set_slot(101 + RELAYER_LIVENESS_SLOTS);
+    take_invoked();
+    assert_eq!(
+        bank.process(&revoke_relayer(
+            authority,
+            authority,
+            relayer_address,
+            new_miner
+        )),
+        Ok(())
+    );
+    assert_eq!(
+        take_invoked(),
+        vec![ore_api::instruction::update(escrow_address, new_miner)]
+    );
+    // Both escrows collected 100 in commission
+    assert_eq!(bank.token_amount(&relayer_vault(relayer_address)), 200);
+    assert_eq!(
+        bank.state::<Relayer>(&relayer_address).accrued_commission,
+        200
+    );
+
+    // The escrow is unbound from the relayer
+    let escrow = bank.state::<Escrow>(&escrow_address);
+    assert_eq!(escrow.last_hash, [3; 32]);
+    assert_eq!(escrow.last_balance, 2_000 - 100);
+    assert_eq!(escrow.commission, 0);
+    assert_eq!(escrow.relayer, Pubkey::default());
+    assert_eq!(bank.state::<Proof>(&proof_address).miner, new_miner);
+    assert_eq!(
+        bank.process(&collect(relayer_miner, relayer_address, authority, 0)),
+        Err(RelayError::InvalidMiner.into())
+    );
+}
+
+#[test]
+fn switch_relayer_after_revoke() {
+    let mut bank = Bank::new();
+    let relayer_authority = Pubkey::new_unique();
+    let relayer_miner = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let relayer_address = set_relayer(&mut bank, relayer_authority, relayer_miner);
+    let (escrow_address, _) = set_escrow(&mut bank, authority, relayer_address, relayer_miner);
+
+    // Escrows which never collected are stale a liveness period after being bound
+    set_slot(RELAYER_LIVENESS_SLOTS + 1);
+    assert_eq!(
+        bank.process(&revoke_relayer(
+            authority,
+            authority,
+            relayer_address,
+            authority
+        )),
+        Ok(())
+    );
+
+    // The unbound escrow cannot be revoked again
+    assert_eq!(
+        bank.process(&revoke_relayer(
+            authority,
+            authority,
+            Pubkey::default(),
+            authority
+        )),
+        Err(RelayError::UnauthorizedRelayer.into())
+    );
+
+    // The unbound escrow may switch to a new relayer, passing the default pubkey as its relayer
+    let new_relayer_authority = Pubkey::new_unique();
+    let new_relayer_miner = Pubkey::new_unique();
+    let new_relayer_address = set_relayer(&mut bank, new_relayer_authority, new_relayer_miner);
+    assert_eq!(
+        bank.process(&switch_relayer(
+            authority,
+            authority,
+            Pubkey::default(),
+            new_relayer_address,
+            new_relayer_miner,
+            1_000
+        )),
+        Ok(())
+    );
+    let escrow = bank.state::<Escrow>(&escrow_address);
+    assert_eq!(escrow.relayer, new_relayer_address);
+    assert_eq!(escrow.relayer_slot, RELAYER_LIVENESS_SLOTS + 1);
+}
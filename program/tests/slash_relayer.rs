@@ -0,0 +1,233 @@
+mod common;
+
+use common::*;
+use ore_api::consts::PROOF;
+use ore_relayer_api::{
+    consts::*,
+    error::RelayError,
+    instruction::*,
+    state::{Escrow, Relayer},
+};
+use solana_program::{
+    ed25519_program, instruction::Instruction, message::Message, pubkey::Pubkey, rent::Rent,
+};
+
+/// Sets up a relayer registered by the relayer authority, and an escrow of the given authority
+/// bound to it, whose proof has mined a new hash. Returns the relayer, the relayer miner and the escrow.
+fn setup(
+    bank: &mut Bank,
+    relayer_authority: Pubkey,
+    authority: Pubkey,
+) -> (Pubkey, Pubkey, Pubkey) {
+    let relayer_miner = Pubkey::new_unique();
+    let (escrow_address, _) =
+        Pubkey::find_program_address(&[ESCROW, authority.as_ref()], &ore_relayer_api::id());
+    set_config(bank);
+    let relayer_address = bank.set_relayer(Relayer {
+        authority: relayer_authority,
+        ..relayer_fixture(relayer_miner)
+    });
+    let rent = Rent::default().minimum_balance(bank.get(&relayer_address).data.len());
+    bank.set_lamports(relayer_address, rent + MIN_RELAYER_BOND);
+    bank.set(
+        escrow_address,
+        ore_relayer_api::id(),
+        account_data(escrow_fixture(authority, relayer_address)),
+    );
+    let (proof_address, _) =
+        Pubkey::find_program_address(&[PROOF, escrow_address.as_ref()], &ore_api::id());
+    bank.set(
+        proof_address,
+        ore_api::id(),
+        account_data(proof_fixture(escrow_address, relayer_miner, 1_000, [2; 32])),
+    );
+    (relayer_address, relayer_miner, escrow_address)
+}
+
+/// Builds an ed25519 instruction verifying the signature of the given key over a transaction
+/// message of the given instructions.
+fn evidence(signer: Pubkey, ixs: &[Instruction]) -> Instruction {
+    let message = Message::new(ixs, Some(&signer)).serialize();
+    let pubkey_offset: u16 = 16;
+    let signature_offset: u16 = 48;
+    let message_offset: u16 = 112;
+    let mut data = vec![1, 0];
+    for offset in [
+        signature_offset,
+        u16::MAX,
+        pubkey_offset,
+        u16::MAX,
+        message_offset,
+        message.len() as u16,
+        u16::MAX,
+    ] {
+        data.extend_from_slice(&offset.to_le_bytes());
+    }
+    data.extend_from_slice(signer.as_ref());
+    data.extend_from_slice(&[0; 64]);
+    data.extend_from_slice(&message);
+    Instruction {
+        program_id: ed25519_program::id(),
+        accounts: vec![],
+        data,
+    }
+}
+
+#[test]
+fn slash_relayer_for_overcharge() {
+    let mut bank = Bank::new();
+    let authority = Pubkey::new_unique();
+    let (relayer_address, relayer_miner, escrow_address) =
+        setup(&mut bank, Pubkey::new_unique(), authority);
+    let slash = slash_relayer(authority, authority, relayer_address);
+
+    // Fees up to the slashable fee are not evidence
+    let collect_ix = collect(relayer_miner, relayer_address, authority, SLASHABLE_FEE);
+    assert_eq!(
+        bank.process_transaction(&[evidence(relayer_miner, &[collect_ix]), slash.clone()]),
+        Err(RelayError::InvalidEvidence.into())
+    );
+
+    // Nor are messages signed by other keys
+    let other = Pubkey::new_unique();
+    let collect_ix = collect(other, relayer_address, authority, SLASHABLE_FEE + 1_000);
+    assert_eq!(
+        bank.process_transaction(&[evidence(other, &[collect_ix]), slash.clone()]),
+        Err(RelayError::InvalidEvidence.into())
+    );
+
+    // Only the overcharge is slashed into the escrow
+    let escrow_lamports = bank.get(&escrow_address).lamports;
+    let relayer_lamports = bank.get(&relayer_address).lamports;
+    let collect_ix = collect(
+        relayer_miner,
+        relayer_address,
+        authority,
+        SLASHABLE_FEE + 1_000,
+    );
+    let collect_evidence = evidence(relayer_miner, &[collect_ix]);
+    assert_eq!(
+        bank.process_transaction(&[collect_evidence.clone(), slash.clone()]),
+        Ok(())
+    );
+    assert_eq!(bank.get(&escrow_address).lamports, escrow_lamports + 1_000);
+    assert_eq!(
+        bank.get(&relayer_address).lamports,
+        relayer_lamports - 1_000
+    );
+    assert_eq!(
+        bank.state::<Relayer>(&relayer_address).bond,
+        MIN_RELAYER_BOND - 1_000
+    );
+    assert_eq!(bank.state::<Escrow>(&escrow_address).slashed, 1_000);
+
+    // The same evidence cannot be slashed twice
+    assert_eq!(
+        bank.process_transaction(&[collect_evidence, slash.clone()]),
+        Err(RelayError::InvalidEvidence.into())
+    );
+
+    // Batched collects are evidence for each escrow they collect
+    let collect_many_ix = collect_many(
+        relayer_miner,
+        relayer_address,
+        &[Pubkey::new_unique(), escrow_address],
+        SLASHABLE_FEE + 3_000,
+    );
+    assert_eq!(
+        bank.process_transaction(&[evidence(relayer_miner, &[collect_many_ix]), slash.clone()]),
+        Ok(())
+    );
+    assert_eq!(bank.get(&escrow_address).lamports, escrow_lamports + 3_000);
+
+    // So are payouts
+    let payout_ix = payout(
+        relayer_miner,
+        relayer_address,
+        authority,
+        Pubkey::new_unique(),
+        SLASHABLE_FEE + 5_000,
+    );
+    assert_eq!(
+        bank.process_transaction(&[evidence(relayer_miner, &[payout_ix]), slash]),
+        Ok(())
+    );
+    assert_eq!(bank.get(&escrow_address).lamports, escrow_lamports + 5_000);
+    assert_eq!(
+        bank.state::<Relayer>(&relayer_address).bond,
+        MIN_RELAYER_BOND - 5_000
+    );
+
+    // Fees allowed by the escrow cap are not evidence
+    let mut escrow = bank.state::<Escrow>(&escrow_address);
+    escrow.max_fee_per_collect = SLASHABLE_FEE + 10_000;
+    bank.set(escrow_address, ore_relayer_api::id(), account_data(escrow));
+    let collect_ix = collect(
+        relayer_miner,
+        relayer_address,
+        authority,
+        SLASHABLE_FEE + 10_000,
+    );
+    assert_eq!(
+        bank.process_transaction(&[
+            evidence(relayer_miner, &[collect_ix]),
+            slash_relayer(authority, authority, relayer_address)
+        ]),
+        Err(RelayError::InvalidEvidence.into())
+    );
+}
+
+#[test]
+fn self_slashing_keeps_bond_locked() {
+    let mut bank = Bank::new();
+    let relayer_authority = Pubkey::new_unique();
+    let (relayer_address, relayer_miner, escrow_address) =
+        setup(&mut bank, relayer_authority, relayer_authority);
+
+    // A relayer slashing itself through its own escrow releases at most the maximum escrow slash
+    let escrow_lamports = bank.get(&escrow_address).lamports;
+    let collect_ix = collect(
+        relayer_miner,
+        relayer_address,
+        relayer_authority,
+        SLASHABLE_FEE + MIN_RELAYER_BOND,
+    );
+    assert_eq!(
+        bank.process_transaction(&[
+            evidence(relayer_miner, &[collect_ix]),
+            slash_relayer(relayer_authority, relayer_authority, relayer_address)
+        ]),
+        Ok(())
+    );
+    assert_eq!(
+        bank.get(&escrow_address).lamports,
+        escrow_lamports + MAX_ESCROW_SLASH
+    );
+    assert_eq!(
+        bank.state::<Relayer>(&relayer_address).bond,
+        MIN_RELAYER_BOND - MAX_ESCROW_SLASH
+    );
+
+    // The relayer may no longer collect with a bond below the minimum
+    assert_eq!(
+        bank.process(&collect(
+            relayer_miner,
+            relayer_address,
+            relayer_authority,
+            0
+        )),
+        Err(RelayError::InsufficientBond.into())
+    );
+
+    // And the rest of its bond stays locked
+    set_slot(100);
+    let relayer_beneficiary = bank.state::<Relayer>(&relayer_address).beneficiary;
+    assert_eq!(
+        bank.process(&deregister_relayer(relayer_authority, relayer_beneficiary)),
+        Ok(())
+    );
+    assert_eq!(
+        bank.process(&deregister_relayer(relayer_authority, relayer_beneficiary)),
+        Err(RelayError::BondLocked.into())
+    );
+}
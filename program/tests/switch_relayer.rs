@@ -0,0 +1,156 @@
+mod common;
+
+use common::*;
+use ore_api::consts::PROOF;
+use ore_relayer_api::{
+    consts::*,
+    error::RelayError,
+    instruction::*,
+    state::{Escrow, Relayer},
+};
+use solana_program::pubkey::Pubkey;
+
+/// Sets up a relayer mining with the given miner, at the given commission rate. Returns the
+/// relayer.
+fn set_relayer(bank: &mut Bank, miner: Pubkey, commission: u64) -> Pubkey {
+    bank.set_relayer(Relayer {
+        commission,
+        accrued_commission: 100,
+        ..relayer_fixture(miner)
+    })
+}
+
+/// Sets up an escrow bound to the given relayer at a commission of 10%, whose proof has mined
+/// 1_000 grains since the last collect. Returns the escrow.
+fn set_escrow(bank: &mut Bank, authority: Pubkey, relayer: Pubkey, miner: Pubkey) -> Pubkey {
+    let (escrow_address, _) =
+        Pubkey::find_program_address(&[ESCROW, authority.as_ref()], &ore_relayer_api::id());
+    let (proof_address, _) =
+        Pubkey::find_program_address(&[PROOF, escrow_address.as_ref()], &ore_api::id());
+    set_config(bank);
+    bank.set(
+        escrow_address,
+        ore_relayer_api::id(),
+        account_data(Escrow {
+            last_hash: [1; 32],
+            last_balance: 1_000,
+            ..escrow_fixture(authority, relayer)
+        }),
+    );
+    bank.set(
+        proof_address,
+        ore_api::id(),
+        account_data(proof_fixture(escrow_address, miner, 2_000, [2; 32])),
+    );
+    escrow_address
+}
+
+#[test]
+fn switch_relayer_settles_commission() {
+    let mut bank = Bank::new();
+    let authority = Pubkey::new_unique();
+    let miner = Pubkey::new_unique();
+    let new_miner = Pubkey::new_unique();
+    let relayer_address = set_relayer(&mut bank, miner, 1_000);
+    let new_relayer_address = set_relayer(&mut bank, new_miner, 500);
+    let escrow_address = set_escrow(&mut bank, authority, relayer_address, miner);
+
+    // The miner and commission must be the new relayer's
+    assert_eq!(
+        bank.process(&switch_relayer(
+            authority,
+            authority,
+            relayer_address,
+            new_relayer_address,
+            miner,
+            500
+        )),
+        Err(RelayError::InvalidMiner.into())
+    );
+    assert_eq!(
+        bank.process(&switch_relayer(
+            authority,
+            authority,
+            relayer_address,
+            new_relayer_address,
+            new_miner,
+            1_000
+        )),
+        Err(RelayError::InvalidCommission.into())
+    );
+
+    // The current relayer must be the one selected by the escrow
+    assert_eq!(
+        bank.process(&switch_relayer(
+            authority,
+            authority,
+            new_relayer_address,
+            new_relayer_address,
+            new_miner,
+            500
+        )),
+        Err(RelayError::UnauthorizedRelayer.into())
+    );
+    assert!(take_invoked().is_empty());
+
+    // Commission on uncollected rewards is claimed to the vault of the current relayer
+    assert_eq!(
+        bank.process(&switch_relayer(
+            authority,
+            authority,
+            relayer_address,
+            new_relayer_address,
+            new_miner,
+            500
+        )),
+        Ok(())
+    );
+    assert_eq!(
+        take_invoked(),
+        vec![
+            ore_api::instruction::claim(escrow_address, relayer_vault(relayer_address), 100),
+            ore_api::instruction::update(escrow_address, new_miner),
+        ]
+    );
+    let escrow = bank.state::<Escrow>(&escrow_address);
+    assert_eq!(escrow.relayer, new_relayer_address);
+    assert_eq!(escrow.commission, 500);
+    assert_eq!(escrow.last_balance, 1_900);
+    assert_eq!(escrow.last_hash, [2; 32]);
+    assert_eq!(
+        bank.state::<Relayer>(&relayer_address).accrued_commission,
+        200
+    );
+    assert_eq!(bank.token_amount(&relayer_vault(relayer_address)), 200);
+}
+
+#[test]
+fn switch_from_deregistered_relayer() {
+    let mut bank = Bank::new();
+    let authority = Pubkey::new_unique();
+    let miner = Pubkey::new_unique();
+    let new_miner = Pubkey::new_unique();
+    let relayer_address = Pubkey::new_unique();
+    let new_relayer_address = set_relayer(&mut bank, new_miner, 500);
+    let escrow_address = set_escrow(&mut bank, authority, relayer_address, miner);
+
+    // Nothing is owed to a relayer which has deregistered
+    assert_eq!(
+        bank.process(&switch_relayer(
+            authority,
+            authority,
+            relayer_address,
+            new_relayer_address,
+            new_miner,
+            500
+        )),
+        Ok(())
+    );
+    assert_eq!(
+        take_invoked(),
+        vec![ore_api::instruction::update(escrow_address, new_miner)]
+    );
+    let escrow = bank.state::<Escrow>(&escrow_address);
+    assert_eq!(escrow.relayer, new_relayer_address);
+    assert_eq!(escrow.last_balance, 2_000);
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use common::*;
+use ore_relayer_api::{consts::*, error::RelayError, instruction::*, state::Escrow};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+
+/// Sets up an escrow opened by the given authority. Returns the escrow address.
+fn setup(bank: &mut Bank, authority: Pubkey) -> Pubkey {
+    let (escrow_address, escrow_bump) =
+        Pubkey::find_program_address(&[ESCROW, authority.as_ref()], &ore_relayer_api::id());
+    bank.set(
+        escrow_address,
+        ore_relayer_api::id(),
+        account_data(Escrow {
+            authority,
+            bump: escrow_bump as u64,
+            version: ESCROW_VERSION,
+            seed_authority: authority,
+            ..Default::default()
+        }),
+    );
+    escrow_address
+}
+
+#[test]
+fn transfer_authority() {
+    let mut bank = Bank::new();
+    let authority = Pubkey::new_unique();
+    let new_authority = Pubkey::new_unique();
+    let escrow_address = setup(&mut bank, authority);
+
+    // Only the current authority may propose a transfer
+    assert_eq!(
+        bank.process(&propose_authority(new_authority, authority, new_authority)),
+        Err(ProgramError::InvalidAccountData)
+    );
+    assert_eq!(
+        bank.process(&propose_authority(authority, authority, new_authority)),
+        Ok(())
+    );
+    let escrow = bank.state::<Escrow>(&escrow_address);
+    assert_eq!(escrow.authority, authority);
+    assert_eq!(escrow.pending_authority, new_authority);
+
+    // Only the pending authority may accept
+    assert_eq!(
+        bank.process(&accept_authority(Pubkey::new_unique(), authority)),
+        Err(RelayError::InvalidPendingAuthority.into())
+    );
+    assert_eq!(
+        bank.process(&accept_authority(new_authority, authority)),
+        Ok(())
+    );
+
+    // The escrow keeps its seeds, and answers to the new authority only
+    let escrow = bank.state::<Escrow>(&escrow_address);
+    assert_eq!(escrow.authority, new_authority);
+    assert_eq!(escrow.seed_authority, authority);
+    assert_eq!(escrow.pending_authority, Pubkey::default());
+    assert_eq!(
+        bank.process(&configure_escrow(authority, authority, 1, 1)),
+        Err(ProgramError::InvalidAccountData)
+    );
+    assert_eq!(
+        bank.process(&configure_escrow(new_authority, authority, 1, 1)),
+        Ok(())
+    );
+
+    // A completed transfer cannot be accepted again
+    assert_eq!(
+        bank.process(&accept_authority(new_authority, authority)),
+        Err(RelayError::InvalidPendingAuthority.into())
+    );
+}
+
+#[test]
+fn accept_authority_clears_previous_settings() {
+    let mut bank = Bank::new();
+    let authority = Pubkey::new_unique();
+    let new_authority = Pubkey::new_unique();
+    let escrow_address = setup(&mut bank, authority);
+
+    // The previous authority registered a payout destination and scheduled a miner change
+    let mut escrow = bank.state::<Escrow>(&escrow_address);
+    escrow.payout_destination = Pubkey::new_unique();
+    escrow.payout_threshold = 1_000;
+    escrow.pending_miner = Pubkey::new_unique();
+    escrow.pending_miner_slot = 100;
+    bank.set(escrow_address, ore_relayer_api::id(), account_data(escrow));
+
+    // Both are cleared once the new authority accepts
+    assert_eq!(
+        bank.process(&propose_authority(authority, authority, new_authority)),
+        Ok(())
+    );
+    assert_eq!(
+        bank.process(&accept_authority(new_authority, authority)),
+        Ok(())
+    );
+    let escrow = bank.state::<Escrow>(&escrow_address);
+    assert_eq!(escrow.payout_destination, Pubkey::default());
+    assert_eq!(escrow.payout_threshold, 0);
+    assert_eq!(escrow.pending_miner, Pubkey::default());
+    assert_eq!(escrow.pending_miner_slot, 0);
+}
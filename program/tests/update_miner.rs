@@ -0,0 +1,165 @@
+mod common;
+
+use common::*;
+use ore_api::{consts::PROOF, state::Proof};
+use ore_relayer_api::{
+    consts::*,
+    instruction::*,
+    state::{Escrow, Relayer},
+};
+use solana_program::pubkey::Pubkey;
+
+/// Sets up an escrow whose proof has mined a hash not yet collected. Returns the escrow and proof.
+fn setup(bank: &mut Bank, authority: Pubkey) -> (Pubkey, Pubkey) {
+    let (escrow_address, escrow_bump) =
+        Pubkey::find_program_address(&[ESCROW, authority.as_ref()], &ore_relayer_api::id());
+    let (proof_address, _) =
+        Pubkey::find_program_address(&[PROOF, escrow_address.as_ref()], &ore_api::id());
+    set_config(bank);
+    bank.set(
+        escrow_address,
+        ore_relayer_api::id(),
+        account_data(Escrow {
+            authority,
+            bump: escrow_bump as u64,
+            last_hash: [1; 32],
+            version: ESCROW_VERSION,
+            seed_authority: authority,
+            ..Default::default()
+        }),
+    );
+    bank.set(
+        proof_address,
+        ore_api::id(),
+        account_data(proof_fixture(
+            escrow_address,
+            Pubkey::new_unique(),
+            1_000,
+            [2; 32],
+        )),
+    );
+    (escrow_address, proof_address)
+}
+
+#[test]
+fn update_miner_after_delay() {
+    let mut bank = Bank::new();
+    let authority = Pubkey::new_unique();
+    let miner = Pubkey::new_unique();
+    let (escrow_address, _) = setup(&mut bank, authority);
+
+    // The change is scheduled while commission on the current hash is uncollected
+    set_slot(100);
+    assert_eq!(
+        bank.process(&update_miner(authority, authority, miner)),
+        Ok(())
+    );
+    assert!(take_invoked().is_empty());
+    let escrow = bank.state::<Escrow>(&escrow_address);
+    assert_eq!(escrow.pending_miner, miner);
+    assert_eq!(escrow.pending_miner_slot, 100 + MINER_CHANGE_DELAY_SLOTS);
+
+    // Calling again before the delay does not restart it, nor apply the change
+    set_slot(101);
+    assert_eq!(
+        bank.process(&update_miner(authority, authority, miner)),
+        Ok(())
+    );
+    assert!(take_invoked().is_empty());
+    assert_eq!(
+        bank.state::<Escrow>(&escrow_address).pending_miner_slot,
+        100 + MINER_CHANGE_DELAY_SLOTS
+    );
+
+    // The change applies once the delay has elapsed
+    set_slot(100 + MINER_CHANGE_DELAY_SLOTS);
+    assert_eq!(
+        bank.process(&update_miner(authority, authority, miner)),
+        Ok(())
+    );
+    assert_eq!(
+        take_invoked(),
+        vec![ore_api::instruction::update(escrow_address, miner)]
+    );
+    let escrow = bank.state::<Escrow>(&escrow_address);
+    assert_eq!(escrow.pending_miner, Pubkey::default());
+    assert_eq!(escrow.pending_miner_slot, 0);
+}
+
+#[test]
+fn update_miner_after_collect() {
+    let mut bank = Bank::new();
+    let authority = Pubkey::new_unique();
+    let miner = Pubkey::new_unique();
+    let (escrow_address, proof_address) = setup(&mut bank, authority);
+
+    // The change applies immediately once commission on the current hash is collected
+    let mut escrow = bank.state::<Escrow>(&escrow_address);
+    escrow.last_hash = bank.state::<Proof>(&proof_address).last_hash;
+    bank.set(escrow_address, ore_relayer_api::id(), account_data(escrow));
+    set_slot(100);
+    assert_eq!(
+        bank.process(&update_miner(authority, authority, miner)),
+        Ok(())
+    );
+    assert_eq!(
+        take_invoked(),
+        vec![ore_api::instruction::update(escrow_address, miner)]
+    );
+    assert_eq!(
+        bank.state::<Escrow>(&escrow_address).pending_miner,
+        Pubkey::default()
+    );
+}
+
+#[test]
+fn update_miner_ready_after_collect() {
+    let mut bank = Bank::new();
+    let authority = Pubkey::new_unique();
+    let miner = Pubkey::new_unique();
+    let (escrow_address, proof_address) = setup(&mut bank, authority);
+
+    // Bind the escrow to a relayer mining its proof
+    let relayer_authority = Pubkey::new_unique();
+    let relayer_miner = bank.state::<Proof>(&proof_address).miner;
+    let relayer_address = bank.set_relayer(Relayer {
+        authority: relayer_authority,
+        ..relayer_fixture(relayer_miner)
+    });
+    let mut escrow = bank.state::<Escrow>(&escrow_address);
+    escrow.relayer = relayer_address;
+    escrow.commission = 1_000;
+    bank.set(escrow_address, ore_relayer_api::id(), account_data(escrow));
+
+    // Schedule a change while commission on the current hash is uncollected
+    set_slot(100);
+    assert_eq!(
+        bank.process(&update_miner(authority, authority, miner)),
+        Ok(())
+    );
+    assert!(take_invoked().is_empty());
+
+    // The relayer collects within the delay. The change stays scheduled.
+    set_slot(110);
+    assert_eq!(
+        bank.process(&collect(relayer_miner, relayer_address, authority, 0)),
+        Ok(())
+    );
+    take_invoked();
+    let escrow = bank.state::<Escrow>(&escrow_address);
+    assert_eq!(escrow.pending_miner, miner);
+    assert_eq!(escrow.pending_miner_slot, 100 + MINER_CHANGE_DELAY_SLOTS);
+
+    // With commission on the current hash collected, the change applies before the delay
+    assert_eq!(
+        bank.process(&update_miner(authority, authority, miner)),
+        Ok(())
+    );
+    assert_eq!(
+        take_invoked(),
+        vec![ore_api::instruction::update(escrow_address, miner)]
+    );
+    let escrow = bank.state::<Escrow>(&escrow_address);
+    assert_eq!(escrow.pending_miner, Pubkey::default());
+    assert_eq!(escrow.pending_miner_slot, 0);
+}
@@ -0,0 +1,186 @@
+mod common;
+
+use common::*;
+use ore_api::consts::PROOF;
+use ore_relayer_api::{
+    consts::*,
+    error::RelayError,
+    instruction::*,
+    state::{Config, Escrow, Relayer},
+};
+use solana_program::pubkey::Pubkey;
+
+/// Sets up a relayer and an escrow bound to it, whose proof has mined the given balance.
+/// Returns the relayer, its beneficiary and the escrow.
+fn setup(
+    bank: &mut Bank,
+    relayer_authority: Pubkey,
+    relayer_miner: Pubkey,
+    authority: Pubkey,
+    proof_balance: u64,
+) -> (Pubkey, Pubkey, Pubkey) {
+    let beneficiary = Pubkey::new_unique();
+    let (escrow_address, _) =
+        Pubkey::find_program_address(&[ESCROW, authority.as_ref()], &ore_relayer_api::id());
+    let (proof_address, _) =
+        Pubkey::find_program_address(&[PROOF, escrow_address.as_ref()], &ore_api::id());
+    set_config(bank);
+    let relayer_address = bank.set_relayer(Relayer {
+        authority: relayer_authority,
+        beneficiary,
+        ..relayer_fixture(relayer_miner)
+    });
+    bank.set(
+        escrow_address,
+        ore_relayer_api::id(),
+        account_data(Escrow {
+            last_hash: [1; 32],
+            ..escrow_fixture(authority, relayer_address)
+        }),
+    );
+    bank.set(
+        proof_address,
+        ore_api::id(),
+        account_data(proof_fixture(
+            escrow_address,
+            relayer_miner,
+            proof_balance,
+            [2; 32],
+        )),
+    );
+    bank.set(
+        beneficiary,
+        spl_token::id(),
+        token_account_data(relayer_authority, 0),
+    );
+    (relayer_address, beneficiary, escrow_address)
+}
+
+#[test]
+fn withdraw_accrued_commission() {
+    let mut bank = Bank::new();
+    let relayer_authority = Pubkey::new_unique();
+    let relayer_miner = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let (relayer_address, beneficiary, escrow_address) = setup(
+        &mut bank,
+        relayer_authority,
+        relayer_miner,
+        authority,
+        1_000,
+    );
+
+    // Collect claims commission into the relayer vault
+    assert_eq!(
+        bank.process(&collect(relayer_miner, relayer_address, authority, 0)),
+        Ok(())
+    );
+    assert_eq!(
+        take_invoked(),
+        vec![ore_api::instruction::claim(
+            escrow_address,
+            relayer_vault(relayer_address),
+            100
+        )]
+    );
+    let escrow = bank.state::<Escrow>(&escrow_address);
+    assert_eq!(escrow.last_balance, 900);
+    assert_eq!(escrow.stats.total_commission, 100);
+    assert_eq!(escrow.stats.collects, 1);
+    assert_eq!(
+        bank.state::<Relayer>(&relayer_address).accrued_commission,
+        100
+    );
+    assert_eq!(bank.token_amount(&relayer_vault(relayer_address)), 100);
+
+    // The relayer cannot deregister while commission is accrued
+    assert_eq!(
+        bank.process(&deregister_relayer(relayer_authority, beneficiary)),
+        Err(RelayError::UnwithdrawnCommission.into())
+    );
+
+    // Only the registered beneficiary may receive the commission
+    let other_beneficiary = Pubkey::new_unique();
+    bank.set(
+        other_beneficiary,
+        spl_token::id(),
+        token_account_data(relayer_authority, 0),
+    );
+    assert_eq!(
+        bank.process(&withdraw_commission(relayer_authority, other_beneficiary)),
+        Err(RelayError::InvalidBeneficiary.into())
+    );
+
+    // Withdraw pays the accrued commission out of the vault in a single transfer
+    assert_eq!(
+        bank.process(&withdraw_commission(relayer_authority, beneficiary)),
+        Ok(())
+    );
+    assert_eq!(
+        take_invoked(),
+        vec![spl_token::instruction::transfer(
+            &spl_token::id(),
+            &relayer_vault(relayer_address),
+            &beneficiary,
+            &relayer_address,
+            &[&relayer_address],
+            100
+        )
+        .unwrap()]
+    );
+    assert_eq!(bank.token_amount(&beneficiary), 100);
+    assert_eq!(bank.token_amount(&relayer_vault(relayer_address)), 0);
+    assert_eq!(
+        bank.state::<Relayer>(&relayer_address).accrued_commission,
+        0
+    );
+
+    // Only the relayer authority may withdraw
+    assert!(bank
+        .process(&withdraw_commission(relayer_miner, beneficiary))
+        .is_err());
+}
+
+#[test]
+fn withdraw_commission_honors_pause() {
+    let mut bank = Bank::new();
+    let relayer_authority = Pubkey::new_unique();
+    let relayer_miner = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let (relayer_address, beneficiary, _) = setup(
+        &mut bank,
+        relayer_authority,
+        relayer_miner,
+        authority,
+        1_000,
+    );
+    assert_eq!(
+        bank.process(&collect(relayer_miner, relayer_address, authority, 0)),
+        Ok(())
+    );
+
+    // Commission cannot be withdrawn while the program is paused
+    let mut config = bank.state::<Config>(&CONFIG_ADDRESS);
+    config.paused = 1;
+    bank.set(CONFIG_ADDRESS, ore_relayer_api::id(), account_data(config));
+    assert_eq!(
+        bank.process(&withdraw_commission(relayer_authority, beneficiary)),
+        Err(RelayError::ProgramPaused.into())
+    );
+
+    // Nor while the relayer is paused
+    config.paused = 0;
+    bank.set(CONFIG_ADDRESS, ore_relayer_api::id(), account_data(config));
+    let mut relayer = bank.state::<Relayer>(&relayer_address);
+    relayer.admin_paused = 1;
+    bank.set(
+        relayer_address,
+        ore_relayer_api::id(),
+        account_data(relayer),
+    );
+    assert_eq!(
+        bank.process(&withdraw_commission(relayer_authority, beneficiary)),
+        Err(RelayError::RelayerPaused.into())
+    );
+    assert_eq!(bank.token_amount(&relayer_vault(relayer_address)), 100);
+}